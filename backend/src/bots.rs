@@ -0,0 +1,172 @@
+mod brain;
+mod coward;
+mod cutter;
+mod food_seeker;
+
+use rand::rngs::OsRng;
+use serde::Deserialize;
+
+use core::{BodyIndex, GameState, Input, SlitherID};
+
+use crate::ids::Ids;
+use crate::state_updater::random_color;
+
+pub use brain::{BotBrain, BrainKind, View};
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BotsConfig {
+    /// The most bots there can be
+    pub count: usize,
+    /// Bots are added while there are fewer slithers in the game,
+    /// so they give place to joining players
+    pub min_population: usize,
+    /// Brains given to the bots in turn, so the populations can be mixed
+    pub brains: Vec<BrainKind>,
+}
+
+impl Default for BotsConfig {
+    fn default() -> Self {
+        Self {
+            count: 5,
+            min_population: 5,
+            brains: vec![BrainKind::FoodSeeker, BrainKind::Coward, BrainKind::Cutter],
+        }
+    }
+}
+
+struct Bot {
+    id: SlitherID,
+    brain: Box<dyn BotBrain>,
+}
+
+/// Slithers controlled by the server
+pub struct Bots {
+    config: BotsConfig,
+    ids: Ids,
+    bots: Vec<Bot>,
+    /// how many bots were spawned since the start, to give them different names and brains
+    spawned: usize,
+}
+
+impl Bots {
+    pub fn new(config: BotsConfig, ids: Ids) -> Self {
+        Self {
+            config,
+            ids,
+            bots: Vec::new(),
+            spawned: 0,
+        }
+    }
+
+    /// Inputs which keep the population and steer the bots
+    pub fn update(&mut self, game_state: &GameState, players: usize) -> Vec<Input> {
+        let mut inputs = Vec::new();
+
+        self.bots
+            .retain(|bot| game_state.world.slithers.exists(bot.id));
+
+        self.steer(game_state, &mut inputs);
+        self.keep_population(players, &mut inputs);
+
+        inputs
+    }
+
+    /// adds or removes one bot at a time, so the population changes smoothly
+    fn keep_population(&mut self, players: usize, inputs: &mut Vec<Input>) {
+        let wanted = self
+            .config
+            .count
+            .min(self.config.min_population.saturating_sub(players));
+
+        if self.bots.len() < wanted && !self.config.brains.is_empty() {
+            let id = self.ids.next();
+            let kind = self.config.brains[self.spawned % self.config.brains.len()];
+
+            self.spawned += 1;
+
+            inputs.push(Input::Join {
+                id,
+                color: random_color(OsRng),
+                nickname: format!("{} {}", kind.name(), self.spawned),
+            });
+
+            self.bots.push(Bot {
+                id,
+                brain: kind.brain(),
+            });
+        } else if self.bots.len() > wanted {
+            let bot = self.bots.pop().unwrap();
+
+            inputs.push(Input::Leave(bot.id));
+        }
+    }
+
+    fn steer(&mut self, game_state: &GameState, inputs: &mut Vec<Input>) {
+        let bodies = BodyIndex::new(&game_state.world.slithers, &game_state.config);
+
+        for bot in &mut self.bots {
+            let view = View {
+                id: bot.id,
+                world: &game_state.world,
+                bodies: &bodies,
+                config: &game_state.config,
+            };
+
+            let decision = bot.brain.decide(&view);
+
+            inputs.push(Input::Direction(bot.id, decision.dir));
+            inputs.push(Input::Boost(bot.id, decision.boost));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{GameConfig, GameState, World};
+
+    use super::{Bots, BotsConfig};
+    use crate::ids::Ids;
+
+    /// lets the bots update `times` times, returns how many bots are in the game then
+    fn settle(bots: &mut Bots, state: &mut GameState, players: usize, times: usize) -> usize {
+        for _ in 0..times {
+            for input in bots.update(state, players) {
+                state.apply(input);
+            }
+        }
+
+        let in_game = state.world.slithers.iter().count();
+
+        assert_eq!(in_game, bots.bots.len());
+
+        in_game
+    }
+
+    #[test]
+    fn bots_fill_the_game_and_give_place_to_players() {
+        let config = BotsConfig {
+            count: 3,
+            min_population: 4,
+            ..Default::default()
+        };
+
+        let mut bots = Bots::new(config, Ids::default());
+
+        let config = GameConfig::default();
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        // one bot at a time
+        assert_eq!(settle(&mut bots, &mut state, 0, 1), 1);
+
+        // no more than `count` even in an empty game
+        assert_eq!(settle(&mut bots, &mut state, 0, 10), 3);
+
+        assert_eq!(settle(&mut bots, &mut state, 2, 10), 2);
+        assert_eq!(settle(&mut bots, &mut state, 4, 10), 0);
+
+        // players left, the bots come back
+        assert_eq!(settle(&mut bots, &mut state, 1, 10), 3);
+    }
+}
@@ -0,0 +1,203 @@
+use emath::{Pos2, Vec2};
+use serde::Deserialize;
+
+use core::{BodyCell, BodyIndex, GameConfig, MassClot, Slither, SlitherID, World};
+
+use super::coward::Coward;
+use super::cutter::Cutter;
+use super::food_seeker::FoodSeeker;
+
+/// Bots turn away from walls and obstacles closer than that
+const WALL_MARGIN: f32 = 100.;
+
+/// Decides where a bot goes
+pub trait BotBrain: Send {
+    fn decide(&mut self, view: &View) -> Decision;
+}
+
+pub struct Decision {
+    pub dir: f32,
+    pub boost: bool,
+}
+
+impl Decision {
+    pub fn go(dir: f32) -> Self {
+        Self { dir, boost: false }
+    }
+
+    pub fn rush(dir: f32) -> Self {
+        Self { dir, boost: true }
+    }
+}
+
+/// The built-in brains, as they are named in the config
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrainKind {
+    FoodSeeker,
+    Coward,
+    Cutter,
+}
+
+impl BrainKind {
+    pub fn brain(self) -> Box<dyn BotBrain> {
+        match self {
+            BrainKind::FoodSeeker => Box::new(FoodSeeker),
+            BrainKind::Coward => Box::new(Coward),
+            BrainKind::Cutter => Box::new(Cutter),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BrainKind::FoodSeeker => "seeker",
+            BrainKind::Coward => "coward",
+            BrainKind::Cutter => "cutter",
+        }
+    }
+}
+
+/// What a bot sees around its slither
+pub struct View<'a> {
+    pub id: SlitherID,
+    pub world: &'a World,
+    pub bodies: &'a BodyIndex,
+    pub config: &'a GameConfig,
+}
+
+impl View<'_> {
+    pub fn slither(&self) -> &Slither {
+        &self.world.slithers[self.id]
+    }
+
+    pub fn head(&self) -> Pos2 {
+        self.slither().body.head()
+    }
+
+    pub fn dir(&self) -> f32 {
+        self.slither().body.dir()
+    }
+
+    pub fn cell_radius(&self) -> f32 {
+        self.slither().body.cell_radius(self.config)
+    }
+
+    /// the shortest way from the head to `pos`, across the edges if the arena wraps
+    pub fn offset(&self, pos: Pos2) -> Vec2 {
+        self.config.arena.delta(self.head(), pos)
+    }
+
+    pub fn distance(&self, pos: Pos2) -> f32 {
+        self.offset(pos).length()
+    }
+
+    /// direction from the head to `pos`
+    pub fn toward(&self, pos: Pos2) -> f32 {
+        self.offset(pos).angle()
+    }
+
+    /// direction from `pos` to the head
+    pub fn away(&self, pos: Pos2) -> f32 {
+        (-self.offset(pos)).angle()
+    }
+
+    /// direction to the center if the head is too close to a wall,
+    /// or away from an obstacle it's too close to
+    pub fn wall_escape(&self) -> Option<f32> {
+        let margin = WALL_MARGIN + self.cell_radius();
+
+        let arena = &self.config.arena;
+
+        if !arena.contains(self.head(), margin) {
+            return Some(self.toward(arena.center()));
+        }
+
+        self.config
+            .obstacles
+            .iter()
+            .find(|obstacle| obstacle.distance(self.head(), arena) < margin)
+            .map(|obstacle| (-obstacle.offset(self.head(), arena)).angle())
+    }
+
+    /// cells of other slithers whose edges are closer to the head than `dist`
+    pub fn bodies(&self, dist: f32) -> impl Iterator<Item = BodyCell> + '_ {
+        let head = self.head();
+        let radius = self.cell_radius();
+
+        self.bodies.near(head, radius + dist).filter(move |cell| {
+            cell.id != self.id && self.distance(cell.pos) < radius + cell.radius + dist
+        })
+    }
+
+    pub fn nearest_body(&self, dist: f32) -> Option<BodyCell> {
+        self.bodies(dist)
+            .min_by(|a, b| self.distance(a.pos).total_cmp(&self.distance(b.pos)))
+    }
+
+    pub fn nearest_food(&self, radius: f32) -> Option<MassClot> {
+        let clots = &self.world.clots;
+
+        self.config
+            .arena
+            .images(self.head(), radius + clots.max_radius())
+            .into_iter()
+            .flat_map(|center| clots.near(center, radius))
+            .min_by(|a, b| self.distance(a.pos).total_cmp(&self.distance(b.pos)))
+    }
+
+    /// goes for the nearest food or keeps the direction
+    pub fn graze(&self, radius: f32) -> Decision {
+        let dir = self
+            .nearest_food(radius)
+            .map_or(self.dir(), |clot| self.toward(clot.pos));
+
+        Decision::go(dir)
+    }
+}
+
+/// A bot alone in the middle of the arena, the scenarios of the brains add what it sees
+#[cfg(test)]
+pub mod scenario {
+    use ecolor::Color32;
+    use emath::Pos2;
+
+    use core::{BodyIndex, GameConfig, GameState, Slither, SlitherID, World};
+
+    use super::{BotBrain, Decision, View};
+
+    pub const BOT: SlitherID = SlitherID(0);
+    pub const HEAD: Pos2 = Pos2::new(1000., 1000.);
+
+    /// the bot heading right
+    pub fn game() -> GameState {
+        let config = GameConfig::default();
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        add_slither(&mut state, BOT, HEAD, 0.);
+
+        state
+    }
+
+    pub fn add_slither(state: &mut GameState, id: SlitherID, pos: Pos2, dir: f32) {
+        let slither = Slither::from_dir(Color32::WHITE, pos, dir, 100., String::new());
+
+        state.world.add_slither(id, slither);
+    }
+
+    pub fn decide(brain: &mut dyn BotBrain, state: &GameState) -> Decision {
+        let bodies = BodyIndex::new(&state.world.slithers, &state.config);
+
+        brain.decide(&View {
+            id: BOT,
+            world: &state.world,
+            bodies: &bodies,
+            config: &state.config,
+        })
+    }
+
+    /// whether the directions are within a few degrees
+    pub fn same_dir(a: f32, b: f32) -> bool {
+        (a - b).cos() > 0.99
+    }
+}
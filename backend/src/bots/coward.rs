@@ -0,0 +1,77 @@
+use emath::Vec2;
+
+use super::brain::{BotBrain, Decision, View};
+
+/// The coward only eats what is close
+const VIEW_RADIUS: f32 = 150.;
+/// It runs from all the bodies closer than that
+const FEAR_DIST: f32 = 150.;
+/// and boosts away from bodies closer than that
+const PANIC_DIST: f32 = 30.;
+
+/// Runs from every body around, eats only when nobody is near
+pub struct Coward;
+
+impl BotBrain for Coward {
+    fn decide(&mut self, view: &View) -> Decision {
+        if let Some(dir) = view.wall_escape() {
+            return Decision::go(dir);
+        }
+
+        // closer cells push harder
+        let escape = view.bodies(FEAR_DIST).fold(Vec2::ZERO, |sum, cell| {
+            let away = -view.offset(cell.pos);
+
+            sum + away / away.length_sq().max(1.)
+        });
+
+        if escape == Vec2::ZERO {
+            return view.graze(VIEW_RADIUS);
+        }
+
+        let dir = escape.angle();
+
+        if view.nearest_body(PANIC_DIST).is_some() {
+            Decision::rush(dir)
+        } else {
+            Decision::go(dir)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use emath::Pos2;
+
+    use core::SlitherID;
+
+    use super::Coward;
+    use crate::bots::brain::scenario::{add_slither, decide, game, same_dir};
+
+    #[test]
+    fn runs_from_bodies() {
+        let mut state = game();
+
+        // a body on the right, close enough to scare but not to panic
+        add_slither(&mut state, SlitherID(1), Pos2::new(1100., 1000.), PI / 2.);
+
+        let decision = decide(&mut Coward, &state);
+
+        assert!(same_dir(decision.dir, PI));
+        assert!(!decision.boost);
+    }
+
+    #[test]
+    fn boosts_away_from_close_bodies() {
+        let mut state = game();
+
+        add_slither(&mut state, SlitherID(1), Pos2::new(1000., 1040.), 0.);
+
+        let decision = decide(&mut Coward, &state);
+
+        assert!(same_dir(decision.dir, -PI / 2.));
+        assert!(decision.boost);
+    }
+}
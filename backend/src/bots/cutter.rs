@@ -0,0 +1,86 @@
+use emath::Vec2;
+
+use super::brain::{BotBrain, Decision, View};
+
+/// How far the cutter looks for victims
+const HUNT_RADIUS: f32 = 400.;
+/// It boosts when the aim is closer
+const RUSH_DIST: f32 = 200.;
+/// It turns away from bodies closer than that
+const DANGER_DIST: f32 = 30.;
+/// How far ahead of a victim the cutter aims, in the victim's cell radii
+const LEAD: f32 = 6.;
+
+/// Tries to cross in front of the nearest head, so the victim runs into its body
+pub struct Cutter;
+
+impl BotBrain for Cutter {
+    fn decide(&mut self, view: &View) -> Decision {
+        if let Some(dir) = view.wall_escape() {
+            return Decision::go(dir);
+        }
+
+        if let Some(cell) = view.nearest_body(DANGER_DIST) {
+            return Decision::go(view.away(cell.pos));
+        }
+
+        let victim = view
+            .world
+            .slithers
+            .iter()
+            .filter(|&(id, _)| id != view.id)
+            .map(|(_, slither)| slither)
+            .filter(|slither| view.distance(slither.body.head()) < HUNT_RADIUS)
+            .min_by(|a, b| {
+                let a = view.distance(a.body.head());
+                let b = view.distance(b.body.head());
+
+                a.total_cmp(&b)
+            });
+
+        let Some(victim) = victim else {
+            return view.graze(HUNT_RADIUS);
+        };
+
+        let lead = LEAD * victim.body.cell_radius(view.config);
+        let aim = victim.body.head() + lead * Vec2::angled(victim.body.dir());
+
+        let dir = view.toward(aim);
+
+        if view.distance(aim) < RUSH_DIST && view.slither().can_boost(view.config) {
+            Decision::rush(dir)
+        } else {
+            Decision::go(dir)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use emath::{Pos2, Vec2};
+
+    use core::SlitherID;
+
+    use super::{Cutter, LEAD};
+    use crate::bots::brain::scenario::{add_slither, decide, game, same_dir, HEAD};
+
+    #[test]
+    fn aims_ahead_of_the_victim() {
+        let mut state = game();
+
+        let victim = SlitherID(1);
+        let up = -PI / 2.;
+
+        add_slither(&mut state, victim, Pos2::new(1200., 1000.), up);
+
+        let body = &state.world.slithers[victim].body;
+        let aim = body.head() + LEAD * body.cell_radius(&state.config) * Vec2::angled(up);
+
+        let decision = decide(&mut Cutter, &state);
+
+        assert!(same_dir(decision.dir, (aim - HEAD).angle()));
+        assert!(!same_dir(decision.dir, (body.head() - HEAD).angle()));
+    }
+}
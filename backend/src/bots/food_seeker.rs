@@ -0,0 +1,53 @@
+use super::brain::{BotBrain, Decision, View};
+
+/// How far the seeker sees food
+const VIEW_RADIUS: f32 = 300.;
+/// It turns away from bodies closer than that
+const DANGER_DIST: f32 = 40.;
+
+/// Goes for the nearest food, keeping off walls and bodies right in front of it
+pub struct FoodSeeker;
+
+impl BotBrain for FoodSeeker {
+    fn decide(&mut self, view: &View) -> Decision {
+        if let Some(dir) = view.wall_escape() {
+            return Decision::go(dir);
+        }
+
+        if let Some(cell) = view.nearest_body(DANGER_DIST) {
+            return Decision::go(view.away(cell.pos));
+        }
+
+        view.graze(VIEW_RADIUS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use emath::Pos2;
+
+    use core::MassClot;
+
+    use super::FoodSeeker;
+    use crate::bots::brain::scenario::{decide, game, same_dir, HEAD};
+
+    #[test]
+    fn goes_for_the_nearest_food() {
+        let mut state = game();
+
+        let near = Pos2::new(1100., 900.);
+        let far = Pos2::new(800., 1200.);
+
+        for pos in [near, far] {
+            state
+                .world
+                .spawn_clot(MassClot::new(pos, 10., Color32::WHITE));
+        }
+
+        let decision = decide(&mut FoodSeeker, &state);
+
+        assert!(same_dir(decision.dir, (near - HEAD).angle()));
+        assert!(!decision.boost);
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use rand::{rngs::OsRng, Rng};
+use serde::Deserialize;
+
+use core::{cli, GameConfig};
+
+use crate::bots::BotsConfig;
+
+/// Everything the server is started with
+#[derive(Deserialize)]
+pub struct ServerConfig {
+    #[serde(flatten)]
+    pub game: GameConfig,
+    #[serde(default)]
+    pub bots: BotsConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            game: GameConfig {
+                seed: OsRng.gen(),
+                ..Default::default()
+            },
+            bots: BotsConfig::default(),
+        }
+    }
+}
+
+/// Reads the config from a TOML file.
+/// Fields missing in the file keep default values, except the seed which is chosen randomly
+pub fn load(path: &Path) -> ServerConfig {
+    let table: toml::Table = cli::load(path, "config");
+
+    let has_seed = table.contains_key("seed");
+
+    let mut config: ServerConfig = match toml::Value::Table(table).try_into() {
+        Ok(config) => config,
+        Err(err) => cli::invalid(path, "config", err),
+    };
+
+    if !has_seed {
+        config.game.seed = OsRng.gen();
+    }
+
+    if let Err(err) = config.game.validate() {
+        cli::invalid(path, "config", err);
+    }
+
+    config
+}
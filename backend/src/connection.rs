@@ -3,14 +3,14 @@ use tokio::sync::{broadcast, mpsc};
 
 use core::SlitherID;
 
-use crate::state_updater::ConnectionMessage;
+use crate::state_updater::{ConnectionMessage, Control};
 use crate::transfer::AsyncReceive;
 
 pub struct Connection {
     pub id: SlitherID,
     pub read_socket: OwnedReadHalf,
 
-    pub directions_tx: mpsc::Sender<(SlitherID, f32)>,
+    pub controls_tx: mpsc::Sender<(SlitherID, Control)>,
     pub connections_tx: mpsc::Sender<ConnectionMessage>,
     pub crash_rx: broadcast::Receiver<SlitherID>,
 }
@@ -28,7 +28,11 @@ impl Connection {
 
             match protocol::ClientUpdate::receive(&mut buffer, &mut self.read_socket).await {
                 Ok(protocol::ClientUpdate::Direction(dir)) => {
-                    self.update_direction(dir).await;
+                    self.control(Control::Direction(dir)).await;
+                }
+
+                Ok(protocol::ClientUpdate::Boost(boost)) => {
+                    self.control(Control::Boost(boost)).await;
                 }
 
                 Ok(protocol::ClientUpdate::Disconnect) => {
@@ -46,8 +50,8 @@ impl Connection {
         }
     }
 
-    async fn update_direction(&mut self, dir: f32) {
-        self.directions_tx.send((self.id, dir)).await.unwrap();
+    async fn control(&mut self, control: Control) {
+        self.controls_tx.send((self.id, control)).await.unwrap();
     }
 
     async fn disconnect(self) {
@@ -0,0 +1,16 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use core::SlitherID;
+
+/// Gives out unique slither ids, shared by the players and the bots
+#[derive(Clone, Default)]
+pub struct Ids {
+    counter: Arc<AtomicU32>,
+}
+
+impl Ids {
+    pub fn next(&self) -> SlitherID {
+        SlitherID(self.counter.fetch_add(1, Ordering::Relaxed))
+    }
+}
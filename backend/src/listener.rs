@@ -4,21 +4,24 @@ use tokio::net::{TcpListener, ToSocketAddrs};
 use tokio::sync::{broadcast, mpsc};
 
 use crate::connection::Connection;
-use crate::state_updater::ConnectionMessage;
+use crate::ids::Ids;
+use crate::state_updater::{ConnectionMessage, Control};
 use crate::transfer::AsyncReceive;
 
 pub struct Listener {
     listener: TcpListener,
+    ids: Ids,
     connections_tx: mpsc::Sender<ConnectionMessage>,
-    directions_tx: mpsc::Sender<(SlitherID, f32)>,
+    controls_tx: mpsc::Sender<(SlitherID, Control)>,
     crash_rx: broadcast::Receiver<SlitherID>,
 }
 
 impl Listener {
     pub async fn start_on(
         addr: impl ToSocketAddrs,
+        ids: Ids,
         connections_tx: mpsc::Sender<ConnectionMessage>,
-        directions_tx: mpsc::Sender<(SlitherID, f32)>,
+        controls_tx: mpsc::Sender<(SlitherID, Control)>,
         crash_rx: broadcast::Receiver<SlitherID>,
     ) -> Self {
         let listener = TcpListener::bind(addr).await.unwrap();
@@ -27,27 +30,21 @@ impl Listener {
 
         Self {
             listener,
+            ids,
             connections_tx,
-            directions_tx,
+            controls_tx,
             crash_rx,
         }
     }
 
     pub async fn listen(self) {
-        let mut ids_counter = 0;
-
-        let mut next_id = || {
-            ids_counter += 1;
-            SlitherID(ids_counter - 1)
-        };
-
         let mut buffer = Vec::new();
 
         loop {
             let (stream, _) = self.listener.accept().await.unwrap();
             let (mut read_socket, write_socket) = stream.into_split();
 
-            let id = next_id();
+            let id = self.ids.next();
 
             let join = protocol::PlayerJoin::receive(&mut buffer, &mut read_socket)
                 .await
@@ -65,7 +62,7 @@ impl Listener {
             let connection = Connection {
                 id,
                 read_socket,
-                directions_tx: self.directions_tx.clone(),
+                controls_tx: self.controls_tx.clone(),
                 connections_tx: self.connections_tx.clone(),
                 crash_rx: self.crash_rx.resubscribe(),
             };
@@ -1,31 +1,89 @@
+mod bots;
+mod config;
 mod connection;
+mod ids;
 mod listener;
+mod map;
+mod recorder;
 mod state_updater;
 mod transfer;
 
-use std::env;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use std::process::exit;
 
-use core::{GameState, World};
+use core::cli::arg;
+use core::{GameState, Map};
 
+use bots::Bots;
+use config::ServerConfig;
+use ids::Ids;
 use listener::Listener;
+use recorder::Recorder;
 use state_updater::StateUpdater;
 use tokio::sync::{broadcast, mpsc};
 
 #[tokio::main]
 async fn main() {
-    let port = port();
+    let port = arg("--port").unwrap_or(0);
+
+    let ServerConfig {
+        game: mut config,
+        bots: mut bots_config,
+    } = arg::<PathBuf>("--config")
+        .map(|path| config::load(&path))
+        .unwrap_or_default();
+
+    if let Some(seed) = arg("--seed") {
+        config.seed = seed;
+    }
+
+    if let Some(tick_rate) = arg::<f32>("--tps") {
+        if !tick_rate.is_finite() || tick_rate <= 0. {
+            eprintln!("invalid tps: \"{tick_rate}\", it must be positive");
+            exit(1);
+        }
+
+        config.tick_rate = tick_rate;
+    }
+
+    if let Some(count) = arg("--bots") {
+        bots_config.count = count;
+    }
+
+    let map = arg::<PathBuf>("--map")
+        .map(|path| map::load(&path))
+        .unwrap_or_else(|| Map::from_config(&config));
+
+    println!("seed: {}", config.seed);
+
+    let ids = Ids::default();
+    let bots = Bots::new(bots_config, ids.clone());
+
+    let game_state = {
+        let rng = config.rng();
+
+        GameState::from_map(config, map, rng)
+    };
+
+    let recorder = arg::<PathBuf>("--record").map(|path| {
+        Recorder::create(&path, &game_state).unwrap_or_else(|err| {
+            eprintln!("can't record to \"{}\": {err}", path.display());
+            exit(1);
+        })
+    });
 
     let (connections_tx, connections_rx) = mpsc::channel(1);
-    let (directions_tx, directions_rx) = mpsc::channel(16);
+    let (controls_tx, controls_rx) = mpsc::channel(16);
     let (crash_tx, crash_rx) = broadcast::channel(16);
 
     let updater = tokio::spawn(
         StateUpdater::new(
-            GameState::new(World::new(2000., 2000., 2000.)),
+            game_state,
+            bots,
+            recorder,
             connections_rx,
-            directions_rx,
+            controls_rx,
             crash_tx,
         )
         .start(),
@@ -35,32 +93,10 @@ async fn main() {
     let addr = SocketAddr::new(ip.into(), port);
 
     let listener = tokio::spawn(
-        Listener::start_on(addr, connections_tx, directions_tx, crash_rx)
+        Listener::start_on(addr, ids, connections_tx, controls_tx, crash_rx)
             .await
             .listen(),
     );
 
     let _ = tokio::join!(updater, listener);
 }
-
-fn port() -> u16 {
-    let mut args = env::args();
-
-    while let Some(arg) = args.next() {
-        if arg == "--port" {
-            let Some(port) = args.next() else {
-                eprintln!("you must specify a port after \"--port\"");
-                exit(1);
-            };
-
-            let Ok(port) = u16::from_str_radix(&port, 10) else {
-                eprintln!("invalid port: \"{}\"", &port);
-                exit(1);
-            };
-
-            return port;
-        }
-    }
-
-    return 0;
-}
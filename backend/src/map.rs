@@ -0,0 +1,14 @@
+use std::path::Path;
+
+use core::{cli, Map};
+
+/// Reads the map from a TOML file. Fields missing in the file keep default values
+pub fn load(path: &Path) -> Map {
+    let map: Map = cli::load(path, "map");
+
+    if let Err(err) = map.validate() {
+        cli::invalid(path, "map", err);
+    }
+
+    map
+}
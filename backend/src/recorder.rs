@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use core::{GameState, Input, ReplayHeader, ReplayRecord, REPLAY_VERSION};
+
+/// How often the whole state is saved, so a replay can be seeked without simulating it from the start
+const KEYFRAME_PERIOD: f32 = 5.;
+
+/// Writes the game to a replay file
+pub struct Recorder {
+    writer: BufWriter<File>,
+    /// applied since the last update
+    inputs: Vec<Input>,
+    keyframe_interval: u64,
+}
+
+impl Recorder {
+    /// Creates the file and writes the header with the current state
+    pub fn create(path: &Path, game_state: &GameState) -> bincode::Result<Self> {
+        let file = File::create(path)?;
+
+        let config = &game_state.config;
+
+        let mut recorder = Self {
+            writer: BufWriter::new(file),
+            inputs: Vec::new(),
+            keyframe_interval: ((config.tick_rate * KEYFRAME_PERIOD) as u64).max(1),
+        };
+
+        let header = ReplayHeader {
+            version: REPLAY_VERSION,
+            config: config.clone(),
+        };
+
+        bincode::serialize_into(&mut recorder.writer, &header)?;
+
+        recorder.keyframe(game_state)?;
+
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, input: &Input) {
+        self.inputs.push(input.clone());
+    }
+
+    /// Writes the inputs recorded since the last update, must be called right before an update
+    pub fn before_update(&mut self) -> bincode::Result<()> {
+        bincode::serialize_into(&mut self.writer, &ReplayRecord::Tick)?;
+        bincode::serialize_into(&mut self.writer, &self.inputs)?;
+
+        self.inputs.clear();
+
+        Ok(())
+    }
+
+    /// Writes a keyframe from time to time, must be called right after an update
+    pub fn after_update(&mut self, game_state: &GameState) -> bincode::Result<()> {
+        if game_state.tick().is_multiple_of(self.keyframe_interval) {
+            self.keyframe(game_state)?;
+        }
+
+        Ok(())
+    }
+
+    fn keyframe(&mut self, game_state: &GameState) -> bincode::Result<()> {
+        bincode::serialize_into(&mut self.writer, &ReplayRecord::Keyframe)?;
+        bincode::serialize_into(&mut self.writer, game_state)?;
+
+        // a replay is readable up to the last keyframe even if the server is killed
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::f32::consts::PI;
 use std::time::Duration;
 
 use ecolor::Color32;
@@ -8,25 +7,29 @@ use rand::{rngs::OsRng, Rng};
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::OwnedWriteHalf;
 use tokio::sync::{broadcast, mpsc};
-use tokio::time::{sleep, Instant};
+use tokio::time::{sleep_until, Instant};
 
-use core::{GameState, Slither, SlitherID};
+use core::{Death, GameEvent, GameState, Input, SlitherID};
 
+use crate::bots::Bots;
+use crate::recorder::Recorder;
 use crate::transfer::AsyncSend;
 
-const INIT_SLITHER_MASS: f32 = 100.;
-const MAX_TPS: f32 = 60.;
-
 pub struct StateUpdater {
     game_state: GameState,
+    bots: Bots,
+    recorder: Option<Recorder>,
     top: VecDeque<SlitherID>,
 
     connections_rx: mpsc::Receiver<ConnectionMessage>,
     connections: HashMap<SlitherID, OwnedWriteHalf>,
 
-    directions_rx: mpsc::Receiver<(SlitherID, f32)>,
+    controls_rx: mpsc::Receiver<(SlitherID, Control)>,
     crash_tx: broadcast::Sender<SlitherID>,
 
+    /// slithers died during the last update
+    died: Vec<Death>,
+
     rng: OsRng,
     buffer: Vec<u8>,
     to_disconnect: HashSet<SlitherID>,
@@ -35,15 +38,20 @@ pub struct StateUpdater {
 impl StateUpdater {
     pub fn new(
         game_state: GameState,
+        bots: Bots,
+        recorder: Option<Recorder>,
         connections_rx: mpsc::Receiver<ConnectionMessage>,
-        directions_rx: mpsc::Receiver<(SlitherID, f32)>,
+        controls_rx: mpsc::Receiver<(SlitherID, Control)>,
         crash_tx: broadcast::Sender<SlitherID>,
     ) -> Self {
         Self {
             game_state,
+            bots,
+            recorder,
             connections_rx,
-            directions_rx,
+            controls_rx,
             crash_tx,
+            died: Default::default(),
             rng: OsRng,
             connections: Default::default(),
             buffer: Default::default(),
@@ -52,29 +60,42 @@ impl StateUpdater {
         }
     }
 
+    /// Advances the game in steps of the same length whatever the load is
     pub async fn start(mut self) {
-        let mut last_tick_dur = 1. / MAX_TPS;
+        let delta_time = self.game_state.config.delta_time();
+        let tick_duration = Duration::from_secs_f32(delta_time);
+
+        let max_ticks = self.game_state.config.max_catch_up_ticks;
+
+        let mut accumulator = Duration::ZERO;
+        let mut last_time = Instant::now();
 
         loop {
-            let tick_start = Instant::now();
+            let now = Instant::now();
 
-            sleep(Duration::from_secs_f32(
-                (1. / MAX_TPS - last_tick_dur).max(0.),
-            ))
-            .await;
+            let ticks = due_ticks(&mut accumulator, now - last_time, tick_duration, max_ticks);
+            last_time = now;
 
-            self.update(last_tick_dur).await;
+            for _ in 0..ticks {
+                self.update(delta_time).await;
+            }
 
-            last_tick_dur = tick_start.elapsed().as_secs_f32();
+            sleep_until(now + tick_duration - accumulator).await;
         }
     }
 
     pub async fn update(&mut self, delta_time: f32) {
-        self.update_directions(delta_time);
+        self.update_controls();
         self.handle_connections().await;
+        self.update_bots();
+
+        self.write_replay(|recorder, _| recorder.before_update());
 
         self.game_state.update(delta_time);
 
+        self.write_replay(Recorder::after_update);
+
+        self.handle_events();
         self.update_top();
 
         self.send().await;
@@ -91,32 +112,21 @@ impl StateUpdater {
                     join,
                     mut write_socket,
                 } => {
-                    let slither = {
-                        let color = join
-                            .color
-                            .map(|color| color.to_opaque())
-                            .unwrap_or_else(|| random_color(&mut self.rng));
-
-                        let world_center = self.game_state.world.center();
-
-                        Slither::from_dir(
-                            color,
-                            world_center,
-                            PI / 2.0,
-                            INIT_SLITHER_MASS,
-                            join.nickname,
-                        )
-                    };
-
-                    self.game_state.world.slithers.add(id, slither);
-
-                    protocol::SessionStart {
-                        world_size: self.game_state.world.size(),
-                        self_id: id,
-                    }
-                    .send(&mut self.buffer, &mut write_socket)
-                    .await
-                    .unwrap();
+                    let color = join
+                        .color
+                        .map(|color| color.to_opaque())
+                        .unwrap_or_else(|| random_color(&mut self.rng));
+
+                    self.apply(Input::Join {
+                        id,
+                        color,
+                        nickname: join.nickname,
+                    });
+
+                    protocol::SessionStart::new(id, &self.game_state.config)
+                        .send(&mut self.buffer, &mut write_socket)
+                        .await
+                        .unwrap();
 
                     self.connections.insert(id, write_socket);
                 }
@@ -128,20 +138,82 @@ impl StateUpdater {
         }
     }
 
-    fn update_directions(&mut self, delta_time: f32) {
-        while let Ok((id, new_dir)) = self.directions_rx.try_recv() {
-            if self.game_state.world.slithers.exists(id) {
-                self.game_state.world.slithers[id].change_dir(new_dir, delta_time);
+    /// A failed write stops the recording, the game goes on without it
+    fn write_replay(
+        &mut self,
+        write: impl FnOnce(&mut Recorder, &GameState) -> bincode::Result<()>,
+    ) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+
+        if let Err(err) = write(recorder, &self.game_state) {
+            eprintln!("the game isn't recorded anymore: {err}");
+            self.recorder = None;
+        }
+    }
+
+    /// every change of the game goes through here, so it can be recorded
+    fn apply(&mut self, input: Input) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&input);
+        }
+
+        self.game_state.apply(input);
+    }
+
+    fn update_controls(&mut self) {
+        while let Ok((id, control)) = self.controls_rx.try_recv() {
+            let input = match control {
+                Control::Direction(new_dir) => Input::Direction(id, new_dir),
+                Control::Boost(boost) => Input::Boost(id, boost),
+            };
+
+            self.apply(input);
+        }
+    }
+
+    fn update_bots(&mut self) {
+        let players = self
+            .connections
+            .keys()
+            .filter(|&&id| self.game_state.world.slithers.exists(id))
+            .count();
+
+        for input in self.bots.update(&self.game_state, players) {
+            self.apply(input);
+        }
+    }
+
+    fn handle_events(&mut self) {
+        self.died.clear();
+
+        for event in self.game_state.drain_events() {
+            if let GameEvent::SlitherDied(death) = event {
+                self.died.push(death);
             }
         }
     }
 
     fn handle_crashed(&mut self) {
-        for &id in &self.game_state.crashed {
-            self.crash_tx.send(id).unwrap();
+        for death in &self.died {
+            self.crash_tx.send(death.id).unwrap();
         }
     }
 
+    /// nickname of an alive slither or of one died during the last update
+    fn nickname(&self, id: SlitherID) -> String {
+        if self.game_state.world.slithers.exists(id) {
+            return self.game_state.world.slithers[id].nickname.clone();
+        }
+
+        self.died
+            .iter()
+            .find(|death| death.id == id)
+            .map(|death| death.nickname.clone())
+            .unwrap()
+    }
+
     fn update_top(&mut self) {
         for (id, slither) in self.game_state.world.slithers.iter() {
             if self
@@ -164,14 +236,25 @@ impl StateUpdater {
 
         bincode::serialize_into(&mut self.buffer, &protocol::ServerUpdate::GameOver).unwrap();
 
-        for &id in &self.game_state.crashed {
-            let write_socket = self.connections.get_mut(&id).unwrap();
-
-            write_socket.write_all(&self.buffer).await.unwrap();
+        for death in &self.died {
+            if let Some(write_socket) = self.connections.get_mut(&death.id) {
+                write_socket.write_all(&self.buffer).await.unwrap();
+            }
         }
 
         self.buffer.clear();
 
+        for death in &self.died {
+            let kill = protocol::Kill {
+                victim: death.nickname.clone(),
+                killer: death.cause.killer().map(|killer| self.nickname(killer)),
+                cause: death.cause,
+            };
+
+            bincode::serialize_into(&mut self.buffer, &protocol::ServerUpdate::Kill).unwrap();
+            bincode::serialize_into(&mut self.buffer, &kill).unwrap();
+        }
+
         bincode::serialize_into(&mut self.buffer, &protocol::ServerUpdate::World).unwrap();
         bincode::serialize_into(&mut self.buffer, &self.game_state.world).unwrap();
         bincode::serialize_into(&mut self.buffer, &protocol::ServerUpdate::PlayersTop).unwrap();
@@ -187,7 +270,7 @@ impl StateUpdater {
     }
 
     fn handle_disconnected(&mut self) {
-        for &id in &self.to_disconnect {
+        for id in std::mem::take(&mut self.to_disconnect) {
             if !self.game_state.world.slithers.exists(id) {
                 continue;
             }
@@ -195,15 +278,40 @@ impl StateUpdater {
             self.connections.remove(&id);
             self.crash_tx.send(id).unwrap();
 
-            let slither = self.game_state.world.slithers.remove(id);
+            self.apply(Input::Leave(id));
+        }
+    }
+}
 
-            self.game_state
-                .world
-                .distribute_slither_mass(slither, &mut self.rng);
+/// Adds the elapsed time to the accumulator and takes from it the time of the ticks which are due.
+/// At most `max_ticks` are taken, the lag beyond them is dropped
+fn due_ticks(
+    accumulator: &mut Duration,
+    elapsed: Duration,
+    tick_duration: Duration,
+    max_ticks: u32,
+) -> u32 {
+    *accumulator += elapsed;
+
+    let mut ticks = 0;
+
+    while *accumulator >= tick_duration {
+        if ticks == max_ticks {
+            *accumulator = Duration::ZERO;
+            break;
         }
 
-        self.to_disconnect.clear();
+        *accumulator -= tick_duration;
+        ticks += 1;
     }
+
+    ticks
+}
+
+/// What a player can do with their slither
+pub enum Control {
+    Direction(f32),
+    Boost(bool),
 }
 
 pub enum ConnectionMessage {
@@ -215,10 +323,52 @@ pub enum ConnectionMessage {
     Disconnected(SlitherID),
 }
 
-fn random_color(mut rng: impl Rng) -> Color32 {
+pub fn random_color(mut rng: impl Rng) -> Color32 {
     Color32::from_rgb(
         rng.gen_range(0..55) + 200,
         rng.gen_range(0..55) + 200,
         rng.gen_range(0..55) + 200,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::due_ticks;
+
+    const TICK: Duration = Duration::from_millis(10);
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn leftover_time_waits_for_the_next_tick() {
+        let mut accumulator = Duration::ZERO;
+
+        assert_eq!(due_ticks(&mut accumulator, ms(25), TICK, 5), 2);
+        assert_eq!(accumulator, ms(5));
+
+        assert_eq!(due_ticks(&mut accumulator, ms(5), TICK, 5), 1);
+        assert_eq!(accumulator, Duration::ZERO);
+    }
+
+    #[test]
+    fn short_stall_is_caught_up() {
+        let mut accumulator = Duration::ZERO;
+
+        assert_eq!(due_ticks(&mut accumulator, ms(40), TICK, 5), 4);
+        assert_eq!(accumulator, Duration::ZERO);
+    }
+
+    #[test]
+    fn long_stall_is_dropped() {
+        let mut accumulator = Duration::ZERO;
+
+        assert_eq!(due_ticks(&mut accumulator, ms(1000), TICK, 5), 5);
+        assert_eq!(accumulator, Duration::ZERO);
+
+        assert_eq!(due_ticks(&mut accumulator, TICK, TICK, 5), 1);
+    }
+}
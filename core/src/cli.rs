@@ -0,0 +1,51 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+
+/// value of the `name` command line argument, exits if it's missing or invalid
+pub fn arg<T: FromStr>(name: &str) -> Option<T> {
+    let mut args = env::args();
+
+    while let Some(arg) = args.next() {
+        if arg == name {
+            let Some(value) = args.next() else {
+                eprintln!("you must specify a value after \"{name}\"");
+                exit(1);
+            };
+
+            let Ok(value) = value.parse() else {
+                eprintln!("invalid {}: \"{}\"", name.trim_start_matches('-'), &value);
+                exit(1);
+            };
+
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Reads a TOML file, exits if it can't be read or parsed.
+/// `kind` names the file in the messages, like "config" or "map"
+pub fn load<T: DeserializeOwned>(path: &Path, kind: &str) -> T {
+    let Ok(text) = fs::read_to_string(path) else {
+        eprintln!("can't read the {kind} file \"{}\"", path.display());
+        exit(1);
+    };
+
+    match toml::from_str(&text) {
+        Ok(value) => value,
+        Err(err) => invalid(path, kind, err),
+    }
+}
+
+/// exits telling what is wrong with the file
+pub fn invalid(path: &Path, kind: &str, err: impl Display) -> ! {
+    eprintln!("invalid {kind} file \"{}\": {}", path.display(), err);
+    exit(1);
+}
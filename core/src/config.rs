@@ -0,0 +1,267 @@
+use std::f32::consts::PI;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Arena, Obstacle};
+
+/// Random generator of the simulation. Its output is fixed for a seed, so runs can be reproduced
+pub type GameRng = ChaCha8Rng;
+
+/// Rules of the game. Missing fields are taken from the default config
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// The same seed with the same inputs always gives the same game
+    pub seed: u64,
+
+    /// Shape and size of the world
+    pub arena: Arena,
+    /// Things inside the world which kill slithers like walls do
+    pub obstacles: Vec<Obstacle>,
+
+    /// How many times per second the game is updated
+    pub tick_rate: f32,
+    /// How many ticks may be simulated at once to catch up after a stall.
+    /// The rest of the lag is dropped, so an overloaded server slows the game down instead of freezing
+    pub max_catch_up_ticks: u32,
+
+    /// Mass of a newly joined slither
+    pub init_slither_mass: f32,
+    /// How far from bodies and walls slithers are spawned, if there is such a place
+    pub spawn_safe_distance: f32,
+    /// How many places are tried to find a safe one
+    pub spawn_candidates: u32,
+    /// How speed relates to mass
+    pub mass_speed_coef: f32,
+    /// A part of the slither's mass which is lost per second of boosted movement
+    pub mass_loss_when_boost: f32,
+    /// Slithers lighter than that can't boost
+    pub min_boost_mass: f32,
+    /// Radians per second
+    pub max_change_dir_speed: f32,
+
+    pub mass_to_area_coef: f32,
+    pub radius_to_dist_coef: f32,
+    pub radius_to_size_coef: f32,
+
+    pub min_clot_mass: f32,
+    pub max_clot_mass: f32,
+
+    /// Total mass of the slithers and the clots per square unit of the world.
+    /// Food is spawned while the world is lighter than that
+    pub target_mass_density: f32,
+    /// Max mass of food spawned per second
+    pub food_spawn_rate: f32,
+    pub food_distribution: FoodDistribution,
+
+    /// What happens when two heads meet
+    pub head_on: HeadOnRule,
+
+    /// Chance per second of a new power-up pickup, while there are fewer than `max_pickups`
+    pub pickup_spawn_rate: f32,
+    pub max_pickups: usize,
+    /// Seconds a power-up lasts
+    pub power_up_duration: f32,
+    /// Food closer to a head than that many cell radii drifts to it
+    pub suction_radius_coef: f32,
+    /// How fast sucked in food drifts
+    pub suction_speed: f32,
+
+    /// How far from the head the magnet pulls food
+    pub magnet_radius: f32,
+    /// How fast the magnet pulls food
+    pub magnet_speed: f32,
+}
+
+/// Where new food appears
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FoodDistribution {
+    /// anywhere in the world with equal chance
+    Uniform,
+    /// in groups of `size` clots within `radius` of a random point
+    Clustered { radius: f32, size: u32 },
+}
+
+/// How a meeting of two heads ends
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadOnRule {
+    BothDie,
+    /// the lighter one dies, both die if they weigh the same
+    LargerSurvives,
+    /// they pass through each other
+    NeitherDies,
+}
+
+impl GameConfig {
+    pub fn rng(&self) -> GameRng {
+        GameRng::seed_from_u64(self.seed)
+    }
+
+    pub fn delta_time(&self) -> f32 {
+        1. / self.tick_rate
+    }
+
+    /// Checks the rules the game can't run with
+    pub fn validate(&self) -> Result<(), String> {
+        self.arena.validate()?;
+
+        let numbers = [
+            ("init_slither_mass", self.init_slither_mass),
+            ("spawn_safe_distance", self.spawn_safe_distance),
+            ("mass_speed_coef", self.mass_speed_coef),
+            ("mass_loss_when_boost", self.mass_loss_when_boost),
+            ("min_boost_mass", self.min_boost_mass),
+            ("max_change_dir_speed", self.max_change_dir_speed),
+            ("min_clot_mass", self.min_clot_mass),
+            ("max_clot_mass", self.max_clot_mass),
+            ("target_mass_density", self.target_mass_density),
+            ("food_spawn_rate", self.food_spawn_rate),
+            ("pickup_spawn_rate", self.pickup_spawn_rate),
+            ("power_up_duration", self.power_up_duration),
+            ("suction_radius_coef", self.suction_radius_coef),
+            ("suction_speed", self.suction_speed),
+            ("magnet_radius", self.magnet_radius),
+            ("magnet_speed", self.magnet_speed),
+        ];
+
+        for (name, value) in numbers {
+            if !value.is_finite() {
+                return Err(format!("{name} must be a number, got {value}"));
+            }
+        }
+
+        // the size of the bodies is divided by them
+        let body_coefs = [
+            ("mass_to_area_coef", self.mass_to_area_coef),
+            ("radius_to_dist_coef", self.radius_to_dist_coef),
+            ("radius_to_size_coef", self.radius_to_size_coef),
+        ];
+
+        for (name, value) in body_coefs {
+            if !value.is_finite() || value <= 0. {
+                return Err(format!("{name} must be positive, got {value}"));
+            }
+        }
+
+        if !self.tick_rate.is_finite() || self.tick_rate <= 0. {
+            return Err(format!(
+                "tick_rate must be positive, got {}",
+                self.tick_rate
+            ));
+        }
+
+        if self.min_clot_mass <= 0. {
+            return Err(format!(
+                "min_clot_mass must be positive, got {}",
+                self.min_clot_mass
+            ));
+        }
+
+        if self.min_clot_mass >= self.max_clot_mass {
+            return Err(format!(
+                "min_clot_mass ({}) must be less than max_clot_mass ({})",
+                self.min_clot_mass, self.max_clot_mass
+            ));
+        }
+
+        if let FoodDistribution::Clustered { radius, size } = self.food_distribution {
+            if size == 0 {
+                return Err("clusters of food must have at least one clot".to_string());
+            }
+
+            if !radius.is_finite() || radius < 0. {
+                return Err(format!(
+                    "radius of food clusters can't be negative, got {radius}"
+                ));
+            }
+        }
+
+        for obstacle in &self.obstacles {
+            obstacle.validate(&self.arena)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            arena: Arena::default(),
+            obstacles: Vec::new(),
+
+            tick_rate: 60.,
+            max_catch_up_ticks: 5,
+
+            init_slither_mass: 100.,
+            spawn_safe_distance: 150.,
+            spawn_candidates: 20,
+            mass_speed_coef: 1000.,
+            mass_loss_when_boost: 0.05,
+            min_boost_mass: 50.,
+            max_change_dir_speed: 8. * PI,
+
+            mass_to_area_coef: 1.,
+            radius_to_dist_coef: 0.2,
+            radius_to_size_coef: 1.,
+
+            min_clot_mass: 10.,
+            max_clot_mass: 25.,
+
+            target_mass_density: 0.0005,
+            food_spawn_rate: 100.,
+            food_distribution: FoodDistribution::Uniform,
+            head_on: HeadOnRule::BothDie,
+
+            pickup_spawn_rate: 0.2,
+            max_pickups: 5,
+            power_up_duration: 10.,
+            suction_radius_coef: 4.,
+            suction_speed: 150.,
+            magnet_radius: 150.,
+            magnet_speed: 300.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameConfig;
+    use crate::Arena;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn sizes_and_coefficients_which_arent_numbers_are_rejected() {
+        let configs = [
+            GameConfig {
+                arena: Arena::Circle { radius: 0. },
+                ..Default::default()
+            },
+            GameConfig {
+                mass_speed_coef: f32::NAN,
+                ..Default::default()
+            },
+            GameConfig {
+                radius_to_dist_coef: 0.,
+                ..Default::default()
+            },
+            GameConfig {
+                max_clot_mass: f32::NAN,
+                ..Default::default()
+            },
+        ];
+
+        for config in configs {
+            assert!(config.validate().is_err());
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{Death, PowerUp, SlitherID};
+
+/// Something happened during the game. Events are collected by [`crate::GameState`]
+/// until they are drained
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    SlitherSpawned {
+        id: SlitherID,
+    },
+    SlitherDied(Death),
+    ClotEaten {
+        id: SlitherID,
+        amount: f32,
+    },
+    BoostStarted {
+        id: SlitherID,
+    },
+    BoostStopped {
+        id: SlitherID,
+    },
+    /// the slither lost mass which became clots: burned while boosting or scattered after death
+    MassDropped {
+        id: SlitherID,
+        amount: f32,
+    },
+    PowerUpPicked {
+        id: SlitherID,
+        power_up: PowerUp,
+    },
+    PowerUpEnded {
+        id: SlitherID,
+        power_up: PowerUp,
+    },
+}
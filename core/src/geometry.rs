@@ -0,0 +1,87 @@
+use emath::{Pos2, Vec2};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Segment {
+    pub start: Pos2,
+    pub end: Pos2,
+}
+
+impl Segment {
+    pub fn new(start: Pos2, end: Pos2) -> Self {
+        Self { start, end }
+    }
+
+    pub fn center(&self) -> Pos2 {
+        self.start.lerp(self.end, 0.5)
+    }
+
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// the point of the segment closest to `point`
+    pub fn closest(&self, point: Pos2) -> Pos2 {
+        let dir = self.end - self.start;
+        let length_sq = dir.length_sq();
+
+        if length_sq == 0. {
+            return self.start;
+        }
+
+        let t = ((point - self.start).dot(dir) / length_sq).clamp(0., 1.);
+
+        self.start + dir * t
+    }
+
+    pub fn distance_sq_to(&self, point: Pos2) -> f32 {
+        self.closest(point).distance_sq(point)
+    }
+
+    /// squared distance between the closest points of the segments
+    pub fn distance_sq(&self, other: &Segment) -> f32 {
+        if self.crosses(other) {
+            return 0.;
+        }
+
+        // otherwise the closest points include an end of one of the segments
+        [
+            self.distance_sq_to(other.start),
+            self.distance_sq_to(other.end),
+            other.distance_sq_to(self.start),
+            other.distance_sq_to(self.end),
+        ]
+        .into_iter()
+        .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Squared least distance between two points which go along the segments
+    /// from the starts to the ends at the same time and with constant speeds
+    pub fn approach_sq(&self, other: &Segment) -> f32 {
+        let offset = self.start - other.start;
+        let relative = (self.end - self.start) - (other.end - other.start);
+
+        let length_sq = relative.length_sq();
+
+        let t = if length_sq == 0. {
+            0.
+        } else {
+            (-offset.dot(relative) / length_sq).clamp(0., 1.)
+        };
+
+        (offset + relative * t).length_sq()
+    }
+
+    /// whether the segments cross each other, touching doesn't count
+    fn crosses(&self, other: &Segment) -> bool {
+        let side = |segment: &Segment, point: Pos2| {
+            cross(segment.end - segment.start, point - segment.start)
+        };
+
+        side(other, self.start) * side(other, self.end) < 0.
+            && side(self, other.start) * side(self, other.end) < 0.
+    }
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use emath::{Pos2, Vec2};
+
+/// Uniform grid which buckets items by the world cell they are placed in.
+///
+/// Buckets are kept in a sorted map so iteration order doesn't depend on
+/// anything but the inserted items.
+#[derive(Clone)]
+pub struct Grid<T> {
+    cell_size: f32,
+    buckets: BTreeMap<(i32, i32), Vec<T>>,
+    len: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            buckets: BTreeMap::new(),
+            len: 0,
+        }
+    }
+
+    pub fn insert(&mut self, pos: Pos2, item: T) {
+        self.buckets.entry(self.key(pos)).or_default().push(item);
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.values().flatten()
+    }
+
+    /// items from every bucket which intersects the bounding box of the circle
+    pub fn near(&self, center: Pos2, radius: f32) -> impl Iterator<Item = &T> {
+        let (min, max) = self.keys_around(center, radius);
+
+        (min.0..=max.0)
+            .flat_map(move |x| self.buckets.range((x, min.1)..=(x, max.1)))
+            .flat_map(|(_, bucket)| bucket)
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut len = 0;
+
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(&mut f);
+            len += bucket.len();
+
+            !bucket.is_empty()
+        });
+
+        self.len = len;
+    }
+
+    /// like [`Grid::retain`], but only visits items returned by [`Grid::near`]
+    pub fn retain_near(&mut self, center: Pos2, radius: f32, mut f: impl FnMut(&T) -> bool) {
+        let (min, max) = self.keys_around(center, radius);
+
+        let mut emptied = Vec::new();
+
+        for x in min.0..=max.0 {
+            for (&key, bucket) in self.buckets.range_mut((x, min.1)..=(x, max.1)) {
+                let old_len = bucket.len();
+
+                bucket.retain(&mut f);
+
+                self.len -= old_len - bucket.len();
+
+                if bucket.is_empty() {
+                    emptied.push(key);
+                }
+            }
+        }
+
+        for key in emptied {
+            self.buckets.remove(&key);
+        }
+    }
+
+    /// like [`Grid::retain_near`], but changes the visited items in place
+    pub fn update_near(&mut self, center: Pos2, radius: f32, mut f: impl FnMut(&mut T)) {
+        let (min, max) = self.keys_around(center, radius);
+
+        for x in min.0..=max.0 {
+            for (_, bucket) in self.buckets.range_mut((x, min.1)..=(x, max.1)) {
+                bucket.iter_mut().for_each(&mut f);
+            }
+        }
+    }
+
+    fn keys_around(&self, center: Pos2, radius: f32) -> ((i32, i32), (i32, i32)) {
+        let min = self.key(center - Vec2::splat(radius));
+        let max = self.key(center + Vec2::splat(radius));
+
+        (min, max)
+    }
+
+    fn key(&self, pos: Pos2) -> (i32, i32) {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::Pos2;
+
+    use super::Grid;
+
+    fn grid() -> Grid<Pos2> {
+        let mut grid = Grid::new(10.);
+
+        for x in -5..5 {
+            for y in -5..5 {
+                let pos = Pos2::new(x as f32 * 7. + 0.5, y as f32 * 7. + 0.5);
+
+                grid.insert(pos, pos);
+            }
+        }
+
+        grid
+    }
+
+    #[test]
+    fn insert_counts_items() {
+        let mut grid = Grid::new(10.);
+
+        assert!(grid.is_empty());
+
+        grid.insert(Pos2::new(-3., 4.), 1);
+        grid.insert(Pos2::new(-3., 4.), 2);
+        grid.insert(Pos2::new(25., -40.), 3);
+
+        assert_eq!(grid.len(), 3);
+
+        let mut items = grid.iter().copied().collect::<Vec<_>>();
+        items.sort();
+
+        assert_eq!(items, [1, 2, 3]);
+    }
+
+    #[test]
+    fn near_finds_everything_in_the_circle() {
+        let grid = grid();
+
+        for center in [Pos2::ZERO, Pos2::new(-20., 13.), Pos2::new(31., -31.)] {
+            let near = grid.near(center, 12.).collect::<Vec<_>>();
+
+            for pos in grid.iter() {
+                if pos.distance(center) <= 12. {
+                    assert!(near.contains(&pos));
+                }
+            }
+
+            assert!(near.len() < grid.len());
+        }
+    }
+
+    #[test]
+    fn retain_near_keeps_far_items() {
+        let mut grid = grid();
+        let center = Pos2::new(3., -8.);
+
+        let all = grid.len();
+        let removed = grid
+            .iter()
+            .filter(|pos| pos.distance(center) <= 12.)
+            .count();
+
+        grid.retain_near(center, 12., |pos| pos.distance(center) > 12.);
+
+        assert_eq!(grid.len(), all - removed);
+        assert_eq!(grid.iter().count(), grid.len());
+        assert!(grid.iter().all(|pos| pos.distance(center) > 12.));
+    }
+
+    #[test]
+    fn update_near_visits_the_same_items_as_near() {
+        let mut grid = grid();
+        let center = Pos2::new(-12., 20.);
+
+        let mut near = grid.near(center, 12.).copied().collect::<Vec<_>>();
+        let mut updated = Vec::new();
+
+        grid.update_near(center, 12., |pos| updated.push(*pos));
+
+        near.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        updated.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+
+        assert_eq!(near, updated);
+        assert!(near.len() < grid.len());
+    }
+}
@@ -0,0 +1,18 @@
+use ecolor::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::SlitherID;
+
+/// Everything from outside that changes the game. Applied in the same order
+/// to the same state, inputs always give the same game
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Input {
+    Join {
+        id: SlitherID,
+        color: Color32,
+        nickname: String,
+    },
+    Leave(SlitherID),
+    Direction(SlitherID, f32),
+    Boost(SlitherID, bool),
+}
@@ -1,7 +1,24 @@
+pub mod cli;
+mod config;
+mod event;
+mod geometry;
+mod grid;
+mod input;
+mod power_up;
+mod replay;
 mod slither;
 mod state;
 mod world;
 
+pub use config::{FoodDistribution, GameConfig, GameRng, HeadOnRule};
+pub use event::GameEvent;
+pub use geometry::Segment;
+pub use input::Input;
+pub use power_up::{Pickup, PowerUp};
+pub use replay::{ReplayHeader, ReplayRecord, REPLAY_VERSION};
 pub use slither::{Slither, SlitherBody};
-pub use state::GameState;
-pub use world::{MassClot, MassClots, SlitherID, World};
+pub use state::{Death, DeathCause, GameState};
+pub use world::{
+    Arena, BodyCell, BodyIndex, FoodZone, Map, MassClot, MassClots, MassLedger, Obstacle, Region,
+    SlitherID, World,
+};
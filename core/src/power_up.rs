@@ -0,0 +1,45 @@
+use emath::Pos2;
+use serde::{Deserialize, Serialize};
+
+/// A temporary ability a slither gets from a [`Pickup`]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUp {
+    /// food around the head is pulled to it
+    Magnet,
+    /// moves as fast as boosting, without losing mass
+    Speed,
+    /// the head passes through other bodies
+    Ghost,
+    /// eaten food weighs twice as much
+    DoubleFood,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 4] = [
+        PowerUp::Magnet,
+        PowerUp::Speed,
+        PowerUp::Ghost,
+        PowerUp::DoubleFood,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::Magnet => "magnet",
+            PowerUp::Speed => "speed",
+            PowerUp::Ghost => "ghost",
+            PowerUp::DoubleFood => "double food",
+        }
+    }
+}
+
+/// A power-up lying in the world, it's taken by touching it with the head
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Pickup {
+    pub pos: Pos2,
+    pub power_up: PowerUp,
+}
+
+impl Pickup {
+    pub const RADIUS: f32 = 10.;
+}
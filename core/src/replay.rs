@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::GameConfig;
+
+/// Replays of other versions can't be read
+pub const REPLAY_VERSION: u32 = 15;
+
+/// Starts a replay file. It is followed by [`ReplayRecord`]s, the first one is a keyframe
+#[derive(Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub version: u32,
+    pub config: GameConfig,
+}
+
+/// Each record is followed by its payload
+#[derive(Serialize, Deserialize)]
+pub enum ReplayRecord {
+    /// followed by `Vec<Input>` which were applied before the next update
+    Tick,
+    /// followed by [`crate::GameState`] as it was after an update
+    Keyframe,
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{ReplayHeader, ReplayRecord, REPLAY_VERSION};
+    use crate::{GameConfig, GameState, Input, Map, SlitherID};
+
+    const KEYFRAME_INTERVAL: u64 = 50;
+
+    /// Plays a game of random inputs and records it the way the server does,
+    /// returns the replay and the final state
+    fn record(config: &GameConfig) -> (Vec<u8>, Vec<u8>) {
+        let mut replay = Vec::new();
+
+        let header = ReplayHeader {
+            version: REPLAY_VERSION,
+            config: config.clone(),
+        };
+
+        bincode::serialize_into(&mut replay, &header).unwrap();
+
+        let rng = config.rng();
+        let mut state = GameState::from_map(config.clone(), Map::from_config(config), rng);
+
+        bincode::serialize_into(&mut replay, &ReplayRecord::Keyframe).unwrap();
+        bincode::serialize_into(&mut replay, &state).unwrap();
+
+        let mut random = StdRng::seed_from_u64(7);
+
+        for tick in 0..300 {
+            let id = SlitherID(random.gen_range(0..6));
+
+            let inputs = match tick % 40 {
+                0 => vec![Input::Join {
+                    id,
+                    color: Color32::WHITE,
+                    nickname: String::new(),
+                }],
+                39 => vec![Input::Leave(id)],
+                _ => vec![
+                    Input::Direction(id, random.gen_range(0.0..std::f32::consts::TAU)),
+                    Input::Boost(id, random.gen_bool(0.2)),
+                ],
+            };
+
+            bincode::serialize_into(&mut replay, &ReplayRecord::Tick).unwrap();
+            bincode::serialize_into(&mut replay, &inputs).unwrap();
+
+            for input in inputs {
+                state.apply(input);
+            }
+
+            state.update(state.config.delta_time());
+
+            if state.tick().is_multiple_of(KEYFRAME_INTERVAL) {
+                bincode::serialize_into(&mut replay, &ReplayRecord::Keyframe).unwrap();
+                bincode::serialize_into(&mut replay, &state).unwrap();
+            }
+        }
+
+        (replay, bincode::serialize(&state).unwrap())
+    }
+
+    #[test]
+    fn replay_plays_the_same_game() {
+        let config = GameConfig {
+            seed: 3,
+            ..Default::default()
+        };
+
+        let (replay, last_state) = record(&config);
+
+        let mut reader = replay.as_slice();
+
+        let header: ReplayHeader = bincode::deserialize_from(&mut reader).unwrap();
+
+        assert_eq!(header.version, REPLAY_VERSION);
+
+        let mut state: Option<GameState> = None;
+        let mut keyframes = 0;
+
+        while !reader.is_empty() {
+            match bincode::deserialize_from(&mut reader).unwrap() {
+                ReplayRecord::Tick => {
+                    let inputs: Vec<Input> = bincode::deserialize_from(&mut reader).unwrap();
+                    let state = state.as_mut().unwrap();
+
+                    for input in inputs {
+                        state.apply(input);
+                    }
+
+                    state.update(state.config.delta_time());
+                }
+
+                ReplayRecord::Keyframe => {
+                    let mut keyframe: GameState = bincode::deserialize_from(&mut reader).unwrap();
+                    keyframe.config = header.config.clone();
+
+                    // the game played from the start is where the recorded one was
+                    if let Some(state) = &state {
+                        assert!(
+                            bincode::serialize(state).unwrap()
+                                == bincode::serialize(&keyframe).unwrap()
+                        );
+                    }
+
+                    state.get_or_insert(keyframe);
+                    keyframes += 1;
+                }
+            }
+        }
+
+        assert_eq!(keyframes, 7);
+        assert!(bincode::serialize(&state.unwrap()).unwrap() == last_state);
+    }
+}
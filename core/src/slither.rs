@@ -1,27 +1,26 @@
-use std::{cmp::Ordering, f32::consts::PI};
+use std::collections::{BTreeMap, VecDeque};
+use std::f32::consts::PI;
 
 use ecolor::Color32;
 use emath::{Pos2, Vec2};
 use serde::{Deserialize, Serialize};
 
-use crate::MassClot;
-
-/// How speed relates to mass
-const MASS_SPEED_COEF: f32 = 1000.;
-/// A percent of the slither's mass is losted during boosted movement
-const MASS_LOSS_WHEN_BOOST: f32 = 0.05;
-const MAX_CHANGE_DIR_SPEED: f32 = 8. * PI;
-
-const MASS_TO_AREA_COEF: f32 = 1.;
-const RADIUS_TO_DIST_COEF: f32 = 0.2;
-const RADIUS_TO_SIZE_COEF: f32 = 1.;
+use crate::geometry::Segment;
+use crate::{GameConfig, MassClot, MassClots, Pickup, PowerUp};
 
 #[derive(Serialize, Deserialize)]
 pub struct Slither {
     pub color: Color32,
+    /// whether the slither is boosting now
     pub boost: bool,
+    /// whether the player asks to boost, it lasts while the slither is too light to do it
+    pub wants_boost: bool,
     pub body: SlitherBody,
     pub nickname: String,
+    /// how many slithers died crashing into this one
+    pub kills: u32,
+    /// active power-ups with seconds left
+    pub power_ups: BTreeMap<PowerUp, f32>,
 }
 
 impl Slither {
@@ -30,44 +29,163 @@ impl Slither {
             color,
             nickname,
             boost: false,
+            wants_boost: false,
             body: SlitherBody::from_dir(pos, dir, mass),
+            kills: 0,
+            power_ups: BTreeMap::new(),
         }
     }
 
-    pub fn do_move(&mut self, delta_time: f32) {
-        self.body.move_on(self.speed() * delta_time);
+    /// starts boosting if the slither is heavy enough, or stops it
+    pub fn set_boost(&mut self, boost: bool, config: &GameConfig) {
+        self.wants_boost = boost;
+        self.boost = boost && self.can_boost(config);
+    }
+
+    pub fn can_boost(&self, config: &GameConfig) -> bool {
+        self.body.mass() >= config.min_boost_mass
+    }
+
+    /// moves with the usual speed, or as fast as boosting with the speed power-up
+    pub fn do_move(&mut self, delta_time: f32, config: &GameConfig) {
+        let speed = if self.has(PowerUp::Speed) {
+            2. * self.speed(config)
+        } else {
+            self.speed(config)
+        };
+
+        self.body.move_on(speed * delta_time, config);
     }
 
     /// moves with 2x speed and returns burned mass clot
-    pub fn move_boosted(&mut self, delta_time: f32) -> f32 {
-        self.body.move_on(2. * self.speed() * delta_time);
+    pub fn move_boosted(&mut self, delta_time: f32, config: &GameConfig) -> f32 {
+        self.body
+            .move_on(2. * self.speed(config) * delta_time, config);
 
-        let lost_mass = MASS_LOSS_WHEN_BOOST * self.body.mass() * delta_time;
+        let lost_mass = config.mass_loss_when_boost * self.body.mass() * delta_time;
 
-        self.body.change_mass_by(-lost_mass);
+        self.body.change_mass_by(-lost_mass, config);
 
         lost_mass
     }
 
-    pub fn try_eat(&mut self, clot: MassClot) -> bool {
-        let max_distance = self.body.cell_radius() + clot.radius();
+    /// eats all the clots touched by the head and returns them
+    pub fn try_eat(&mut self, clots: &mut MassClots, config: &GameConfig) -> Vec<MassClot> {
+        let head = self.body.head();
+        let cell_radius = self.body.cell_radius(config);
+
+        let mut eaten_clots = Vec::new();
+
+        for head in config.arena.images(head, cell_radius + clots.max_radius()) {
+            clots.retain_near(head, cell_radius, |clot| {
+                let max_distance = cell_radius + clot.radius();
+
+                let eaten = head.distance_sq(clot.pos) < max_distance.powi(2);
+
+                if eaten {
+                    eaten_clots.push(clot);
+                }
+
+                !eaten
+            });
+        }
+
+        let eaten_mass = eaten_clots.iter().map(|clot| clot.amount).sum();
+
+        self.body.change_mass_by(eaten_mass, config);
+
+        eaten_clots
+    }
+
+    /// takes the pickups touched by the head and returns their power-ups
+    pub fn try_pick_up(&mut self, pickups: &mut Vec<Pickup>, config: &GameConfig) -> Vec<PowerUp> {
+        let head = self.body.head();
+        let max_distance = self.body.cell_radius(config) + Pickup::RADIUS;
+
+        let mut picked = Vec::new();
+
+        pickups.retain(|pickup| {
+            let touched = config.arena.distance(head, pickup.pos) < max_distance;
 
-        let eaten = self.body.head().distance_sq(clot.pos) < max_distance.powi(2);
+            if touched {
+                picked.push(pickup.power_up);
+            }
+
+            !touched
+        });
+
+        for &power_up in &picked {
+            self.grant(power_up, config.power_up_duration);
+        }
+
+        picked
+    }
 
-        if eaten {
-            self.body.change_mass_by(clot.amount);
+    /// Sucks the clots around the head in, so they drift toward it to be eaten.
+    /// The magnet power-up pulls from farther and faster. The clots move after all the heads pulled them
+    pub fn attract(&self, clots: &mut MassClots, delta_time: f32, config: &GameConfig) {
+        let suction_radius = config.suction_radius_coef * self.body.cell_radius(config);
 
-            true
+        let (radius, speed) = if self.has(PowerUp::Magnet) {
+            (
+                config.magnet_radius.max(suction_radius),
+                config.magnet_speed.max(config.suction_speed),
+            )
         } else {
-            false
+            (suction_radius, config.suction_speed)
+        };
+
+        for head in config
+            .arena
+            .images(self.body.head(), radius + clots.max_radius())
+        {
+            clots.pull_near(head, radius, |clot| {
+                let to_head = head - clot.pos;
+                let distance = to_head.length();
+
+                if distance >= radius || distance == 0. {
+                    return Vec2::ZERO;
+                }
+
+                // the clot stops at the head instead of flying past it
+                to_head * (speed / distance).min(1. / delta_time)
+            });
         }
     }
 
-    pub fn speed(&self) -> f32 {
-        MASS_SPEED_COEF / self.body.mass().cbrt()
+    pub fn has(&self, power_up: PowerUp) -> bool {
+        self.power_ups.contains_key(&power_up)
+    }
+
+    /// gives the power-up for `duration` seconds, or lets an active one last at least as long
+    pub fn grant(&mut self, power_up: PowerUp, duration: f32) {
+        let left = self.power_ups.entry(power_up).or_insert(0.);
+
+        *left = left.max(duration);
+    }
+
+    /// counts the time of the power-ups down and returns those which ended
+    pub fn wear_off(&mut self, delta_time: f32) -> Vec<PowerUp> {
+        let mut ended = Vec::new();
+
+        self.power_ups.retain(|&power_up, left| {
+            *left -= delta_time;
+
+            if *left <= 0. {
+                ended.push(power_up);
+            }
+
+            *left > 0.
+        });
+
+        ended
+    }
+
+    pub fn speed(&self, config: &GameConfig) -> f32 {
+        config.mass_speed_coef / self.body.mass().cbrt()
     }
 
-    pub fn change_dir(&mut self, new_dir: f32, delta_time: f32) {
+    pub fn change_dir(&mut self, new_dir: f32, delta_time: f32, config: &GameConfig) {
         let old_dir = self.body.dir;
 
         let old_dir = old_dir.rem_euclid(2. * PI);
@@ -82,8 +200,8 @@ impl Slither {
         };
 
         let delta_dir = delta_dir.clamp(
-            -MAX_CHANGE_DIR_SPEED * delta_time,
-            MAX_CHANGE_DIR_SPEED * delta_time,
+            -config.max_change_dir_speed * delta_time,
+            config.max_change_dir_speed * delta_time,
         );
 
         let new_dir = (old_dir + delta_dir).rem_euclid(2. * PI);
@@ -92,9 +210,14 @@ impl Slither {
     }
 }
 
+/// The head leaves a trail of its positions and the cells follow it exactly `cells_dist` apart
 #[derive(Serialize, Deserialize)]
 pub struct SlitherBody {
     dir: f32,
+    /// Past positions of the head, the newest first, as long as the body.
+    /// Clients draw only the cells, so it's kept only in the snapshots of the game
+    #[serde(skip)]
+    trail: VecDeque<Pos2>,
     cells: Vec<Pos2>,
     mass: f32,
 }
@@ -103,11 +226,16 @@ impl SlitherBody {
     pub fn from_dir(pos: Pos2, dir: f32, mass: f32) -> Self {
         Self {
             dir,
+            trail: VecDeque::from([pos]),
             cells: vec![pos],
             mass,
         }
     }
 
+    pub fn dir(&self) -> f32 {
+        self.dir
+    }
+
     pub fn head(&self) -> Pos2 {
         self.cells[0]
     }
@@ -120,106 +248,184 @@ impl SlitherBody {
         &self.cells
     }
 
-    pub fn change_mass_by(&mut self, mass: f32) {
-        self.mass += mass;
+    pub(crate) fn trail(&self) -> &VecDeque<Pos2> {
+        &self.trail
+    }
 
-        self.resize();
+    pub(crate) fn restore_trail(&mut self, trail: VecDeque<Pos2>) {
+        self.trail = trail;
     }
 
-    pub fn resize(&mut self) {
-        if self.cells.len() == self.size() {
-            return;
-        }
+    /// the way the head went during the last move, it may start outside a wrapping arena
+    pub fn head_path(&self, config: &GameConfig) -> Segment {
+        let head = self.head();
+        let from = self.trail.get(1).copied().unwrap_or(head);
+
+        Segment::new(head - config.arena.delta(from, head), head)
+    }
+
+    /// Parts of the body between neighbour cells with the cell index,
+    /// a single cell is a part of zero length.
+    /// A part crossing the edge of a wrapping arena ends outside of it
+    pub fn segments<'a>(
+        &'a self,
+        config: &'a GameConfig,
+    ) -> impl Iterator<Item = (usize, Segment)> + 'a {
+        let next = self.cells.iter().skip(1).chain(self.cells.last());
 
         self.cells
-            .resize(self.size(), self.cells.last().copied().unwrap());
+            .iter()
+            .zip(next)
+            .enumerate()
+            .take(self.cells.len().saturating_sub(1).max(1))
+            .map(|(index, (&start, &end))| {
+                (
+                    index,
+                    Segment::new(start, start + config.arena.delta(start, end)),
+                )
+            })
+    }
+
+    pub fn change_mass_by(&mut self, mass: f32, config: &GameConfig) {
+        self.mass += mass;
+
+        self.resize(config);
+    }
+
+    /// places the cells for the current mass
+    pub fn resize(&mut self, config: &GameConfig) {
+        self.place_cells(config);
     }
 
     pub fn mass(&self) -> f32 {
         self.mass
     }
 
-    pub fn size(&self) -> usize {
-        (MASS_TO_AREA_COEF * self.mass()
+    pub fn size(&self, config: &GameConfig) -> usize {
+        (config.mass_to_area_coef * self.mass()
             / 2.
-            / RADIUS_TO_DIST_COEF
-            / RADIUS_TO_SIZE_COEF.powf(3. / 2.))
+            / config.radius_to_dist_coef
+            / config.radius_to_size_coef.powf(3. / 2.))
         .powf(2. / 7.)
         .floor() as usize
     }
 
-    pub fn cell_radius(&self) -> f32 {
-        (MASS_TO_AREA_COEF * self.mass() / 2.0 / RADIUS_TO_DIST_COEF / RADIUS_TO_SIZE_COEF)
+    pub fn cell_radius(&self, config: &GameConfig) -> f32 {
+        (config.mass_to_area_coef * self.mass()
+            / 2.0
+            / config.radius_to_dist_coef
+            / config.radius_to_size_coef)
             .powf(3. / 8.)
     }
 
-    pub fn cells_dist(&self) -> f32 {
-        self.cell_radius()
+    pub fn cells_dist(&self, config: &GameConfig) -> f32 {
+        self.cell_radius(config)
     }
 
-    fn move_on(&mut self, dist: f32) {
-        for n in (1..self.cells.len()).rev() {
-            let prev = self.cells[n - 1];
-            let current = self.cells[n];
+    fn move_on(&mut self, dist: f32, config: &GameConfig) {
+        let head = config
+            .arena
+            .wrap(self.head() + dist * Vec2::angled(self.dir));
 
-            let distance = prev.distance(current);
+        self.trail.push_front(head);
 
-            match distance.total_cmp(&self.cells_dist()) {
-                Ordering::Less => {
-                    self.move_nth(n, dist * 0.5);
-                }
-                Ordering::Equal => {
-                    self.move_nth(n, dist);
-                }
-                Ordering::Greater => {
-                    self.move_nth(n, dist * 1.2);
-                }
+        self.place_cells(config);
+    }
+
+    /// Puts the cells along the trail and forgets the trail behind the last one.
+    /// Cells which don't fit on the trail yet wait at its end, so a growing body grows from the tail
+    fn place_cells(&mut self, config: &GameConfig) {
+        let size = self.size(config).max(1);
+        let spacing = self.cells_dist(config);
+
+        self.cells.clear();
+        self.cells.push(self.trail[0]);
+
+        // length of the trail before the current segment
+        let mut walked = 0.;
+        let mut segment = 0;
+
+        while self.cells.len() < size && segment + 1 < self.trail.len() {
+            let start = self.trail[segment];
+            let end = self.trail[segment + 1];
+
+            let delta = config.arena.delta(start, end);
+            let length = delta.length();
+            let target = self.cells.len() as f32 * spacing;
+
+            if length > 0. && target <= walked + length {
+                let cell = start + delta * ((target - walked) / length);
+
+                self.cells.push(config.arena.wrap(cell));
+            } else {
+                walked += length;
+                segment += 1;
             }
         }
 
-        self.cells[0] += dist * Vec2::angled(self.dir);
+        self.trail.truncate(segment + 2);
+
+        let end = *self.trail.back().unwrap();
+
+        self.cells.resize(size, end);
     }
 
-    fn move_nth(&mut self, n: usize, mut delta_dist: f32) {
-        self.cells[n] = self.cells[0..=n]
-            .iter()
-            .enumerate()
-            .skip(1)
-            .rev()
-            .find_map(|(n, &cell)| {
-                let nth_dist = self.get_nth_dist(n);
+    /// Whether the head touched the other body anywhere on its way during the last move,
+    /// so a fast head can't jump over a thin body
+    pub fn crashed_into(&self, other: &SlitherBody, config: &GameConfig) -> bool {
+        let safe_dist = other.cell_radius(config) + self.cell_radius(config);
+        let path = self.head_path(config);
 
-                if delta_dist > nth_dist.length() {
-                    delta_dist -= nth_dist.length();
+        other.segments(config).any(|(_, segment)| {
+            let segment = config.arena.align(segment, path.end);
 
-                    None
-                } else {
-                    Some(cell + nth_dist.normalized() * delta_dist)
-                }
-            })
-            .unwrap_or_else(|| {
-                let head = self.cells[0];
+            path.distance_sq(&segment) < safe_dist.powi(2)
+        })
+    }
+}
 
-                head + Vec2::angled(self.dir) * delta_dist
-            });
+#[cfg(test)]
+mod tests {
+    use emath::Pos2;
+
+    use super::SlitherBody;
+    use crate::GameConfig;
+
+    fn run_straight(ticks: u32, delta_time: f32) -> SlitherBody {
+        let config = GameConfig::default();
+
+        let mut body = SlitherBody::from_dir(Pos2::ZERO, 0., 1000.);
+
+        for _ in 0..ticks {
+            body.resize(&config);
+            body.move_on(100. * delta_time, &config);
+        }
+
+        body
     }
 
-    fn get_nth_dist(&self, n: usize) -> Vec2 {
-        assert!(n > 0);
+    #[test]
+    fn cells_are_evenly_spaced() {
+        let config = GameConfig::default();
+
+        let body = run_straight(120, 1. / 60.);
 
-        let prev = self.cells[n - 1];
-        let current = self.cells[n];
+        let spacing = body.cells_dist(&config);
 
-        prev - current
+        for pair in body.cells().windows(2) {
+            assert!((pair[0].distance(pair[1]) - spacing).abs() < 1e-3);
+        }
     }
 
-    pub fn crashed_into(&self, other: &SlitherBody) -> bool {
-        let safe_dist = other.cell_radius() + self.cell_radius();
+    #[test]
+    fn same_body_at_any_tick_rate() {
+        let slow = run_straight(60, 1. / 30.);
+        let fast = run_straight(240, 1. / 120.);
 
-        other
-            .cells
-            .iter()
-            .find(|&&cell| self.head().distance_sq(cell) < safe_dist.powi(2))
-            .is_some()
+        assert_eq!(slow.cells().len(), fast.cells().len());
+
+        for (a, b) in slow.cells().iter().zip(fast.cells()) {
+            assert!(a.distance(*b) < 1e-2);
+        }
     }
 }
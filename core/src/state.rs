@@ -1,87 +1,1151 @@
-use emath::{Rect, Vec2};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::vec::Drain;
 
-use crate::world::World;
-use crate::{MassClot, SlitherID};
+use ecolor::Color32;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::world::{BodyCell, BodyIndex, FoodSpawner, Map, Region, World};
+use crate::{
+    GameConfig, GameEvent, GameRng, HeadOnRule, Input, MassClot, Pickup, PowerUp, Slither,
+    SlitherID,
+};
+
+/// Serialized without the config and the events, the config is stored separately
+#[derive(Serialize, Deserialize)]
 pub struct GameState {
+    #[serde(skip)]
+    pub config: GameConfig,
+    #[serde(with = "crate::world::snapshot")]
     pub world: World,
-    pub crashed: Vec<SlitherID>,
+
+    /// happened since the last drain
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+
+    food_spawner: FoodSpawner,
+    /// where slithers appear, anywhere in the arena if there are none
+    spawn_regions: Vec<Region>,
+
+    /// number of updates done since the game started
+    tick: u64,
+
+    /// the only source of randomness of the simulation
+    rng: GameRng,
 }
 
 impl GameState {
-    pub fn new(world: World) -> Self {
+    pub fn new(config: GameConfig, world: World, rng: GameRng) -> Self {
         Self {
+            config,
             world,
-            crashed: Vec::new(),
+            events: Vec::new(),
+            food_spawner: FoodSpawner::default(),
+            spawn_regions: Vec::new(),
+            tick: 0,
+            rng,
+        }
+    }
+
+    /// The game on the map, its world starts with the food of the map spread over the food zones.
+    /// The arena and the obstacles of the map replace the ones of the config
+    pub fn from_map(mut config: GameConfig, map: Map, mut rng: GameRng) -> Self {
+        config.arena = map.arena;
+        config.obstacles = map.obstacles;
+
+        let food_spawner = FoodSpawner::new(map.food_mass, map.food_zones);
+
+        let mut world = World::default();
+        food_spawner.fill(&mut world, &config, &mut rng);
+
+        Self {
+            food_spawner,
+            spawn_regions: map.spawn_regions,
+            ..Self::new(config, world, rng)
         }
     }
 
     pub fn update(&mut self, delta_time: f32) {
+        self.powering(delta_time);
         self.moving(delta_time);
-        self.eating();
+        self.eating(delta_time);
         self.crashings();
+        self.spawning(delta_time);
+
+        debug_assert!(
+            self.world.ledger.balances(&self.world),
+            "mass isn't conserved: {:?}, world mass {}",
+            self.world.ledger,
+            self.world.mass()
+        );
+
+        self.tick += 1;
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// takes all the events happened since the previous call
+    pub fn drain_events(&mut self) -> Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    /// inputs of slithers which aren't in the game are ignored,
+    /// as well as joins of slithers which already are
+    pub fn apply(&mut self, input: Input) {
+        match input {
+            Input::Join {
+                id,
+                color,
+                nickname,
+            } if !self.world.slithers.exists(id) => self.spawn_slither(id, color, nickname),
+
+            Input::Leave(id) if self.world.slithers.exists(id) => self.remove_slither(id),
+
+            Input::Direction(id, dir) if self.world.slithers.exists(id) => {
+                self.world.slithers[id].change_dir(dir, self.config.delta_time(), &self.config);
+            }
+
+            Input::Boost(id, boost) if self.world.slithers.exists(id) => self.set_boost(id, boost),
+
+            _ => {}
+        }
+    }
+
+    /// starts or stops boosting, light slithers can't start
+    pub fn set_boost(&mut self, id: SlitherID, boost: bool) {
+        let slither = &mut self.world.slithers[id];
+
+        let was_boosting = slither.boost;
+
+        slither.set_boost(boost, &self.config);
+
+        match (was_boosting, slither.boost) {
+            (false, true) => self.events.push(GameEvent::BoostStarted { id }),
+            (true, false) => self.events.push(GameEvent::BoostStopped { id }),
+            _ => {}
+        }
+    }
+
+    /// adds a new slither at a safe place of the world
+    pub fn spawn_slither(&mut self, id: SlitherID, color: Color32, nickname: String) {
+        let (pos, dir) = self
+            .world
+            .spawn_point(&mut self.rng, &self.spawn_regions, &self.config);
+
+        let slither = Slither::from_dir(color, pos, dir, self.config.init_slither_mass, nickname);
+
+        self.world.add_slither(id, slither);
+
+        self.events.push(GameEvent::SlitherSpawned { id });
+    }
+
+    /// removes the slither and scatters its mass over the world
+    pub fn remove_slither(&mut self, id: SlitherID) {
+        let slither = self.world.slithers.remove(id);
+
+        self.events.push(GameEvent::MassDropped {
+            id,
+            amount: slither.body.mass(),
+        });
+
+        self.world
+            .distribute_slither_mass(slither, &self.config, &mut self.rng);
     }
 
     fn moving(&mut self, delta_time: f32) {
-        for (_, slither) in self.world.slithers.iter_mut() {
-            slither.body.resize();
+        for (id, slither) in self.world.slithers.iter_mut() {
+            slither.body.resize(&self.config);
+
+            // a player holding the boost stops when the slither gets too light
+            // and goes on when it gets heavy enough again
+            let boost = slither.wants_boost && slither.can_boost(&self.config);
+
+            match (slither.boost, boost) {
+                (false, true) => self.events.push(GameEvent::BoostStarted { id }),
+                (true, false) => self.events.push(GameEvent::BoostStopped { id }),
+                _ => {}
+            }
 
-            if slither.boost {
-                let lost_mass = slither.move_boosted(delta_time);
+            slither.boost = boost;
 
-                self.world.clots.add(MassClot {
-                    pos: slither.body.end(),
+            // the speed power-up makes a slither fast anyway, so it doesn't burn anything
+            if slither.boost && !slither.has(PowerUp::Speed) {
+                let lost_mass = slither.move_boosted(delta_time, &self.config);
+
+                self.events.push(GameEvent::MassDropped {
+                    id,
                     amount: lost_mass,
-                    color: slither.color,
                 });
+
+                self.world.ledger.burned += lost_mass as f64;
+
+                self.world
+                    .clots
+                    .add(MassClot::new(slither.body.end(), lost_mass, slither.color));
             } else {
-                slither.do_move(delta_time);
+                slither.do_move(delta_time, &self.config);
             }
         }
     }
 
-    fn eating(&mut self) {
-        self.world.clots.retain(|clot| {
-            for (_, slither) in self.world.slithers.iter_mut() {
-                if slither.try_eat(clot) {
-                    return false;
-                }
+    /// counts the power-ups down and takes away the ended ones
+    fn powering(&mut self, delta_time: f32) {
+        for (id, slither) in self.world.slithers.iter_mut() {
+            for power_up in slither.wear_off(delta_time) {
+                self.events.push(GameEvent::PowerUpEnded { id, power_up });
             }
+        }
+    }
 
-            true
-        });
+    fn eating(&mut self, delta_time: f32) {
+        self.world.clots.settle();
+
+        for (id, slither) in self.world.slithers.iter_mut() {
+            for power_up in slither.try_pick_up(&mut self.world.pickups, &self.config) {
+                self.events.push(GameEvent::PowerUpPicked { id, power_up });
+            }
+
+            slither.attract(&mut self.world.clots, delta_time, &self.config);
+        }
+
+        // a clot between two heads moves once, pulled by both of them
+        self.world.clots.drift(delta_time, &self.config.arena);
+
+        for (id, slither) in self.world.slithers.iter_mut() {
+            let mut eaten_mass = 0.;
+
+            for clot in slither.try_eat(&mut self.world.clots, &self.config) {
+                self.world.ledger.eaten += clot.amount as f64;
+                eaten_mass += clot.amount;
+
+                self.events.push(GameEvent::ClotEaten {
+                    id,
+                    amount: clot.amount,
+                });
+            }
+
+            if eaten_mass > 0. && slither.has(PowerUp::DoubleFood) {
+                self.world.ledger.bonus += eaten_mass as f64;
+
+                slither.body.change_mass_by(eaten_mass, &self.config);
+            }
+        }
+    }
+
+    fn spawning(&mut self, delta_time: f32) {
+        self.food_spawner
+            .update(&mut self.world, &self.config, &mut self.rng, delta_time);
+
+        let chance = (self.config.pickup_spawn_rate * delta_time).clamp(0., 1.);
+
+        if self.world.pickups.len() < self.config.max_pickups && self.rng.gen_bool(chance as f64) {
+            let pos = self.config.random_pos(&mut self.rng, Pickup::RADIUS);
+            let &power_up = PowerUp::ALL.choose(&mut self.rng).unwrap();
+
+            self.world.pickups.push(Pickup { pos, power_up });
+        }
     }
 
     fn crashings(&mut self) {
-        self.crashed.clear();
+        let bodies = BodyIndex::new(&self.world.slithers, &self.config);
+
+        // pairs of slithers whose heads met, the smaller id first
+        let mut head_ons = BTreeSet::new();
 
         for (id, slither) in self.world.slithers.iter() {
-            let offset = Vec2::splat(slither.body.cell_radius());
+            for other in bodies.met_heads(id, &slither.body, &self.config) {
+                head_ons.insert((id.min(other), id.max(other)));
+            }
+        }
+
+        // everything is decided by the state before the crashes, so the order doesn't matter
+        let mut crashes = BTreeMap::new();
+
+        for (id, slither) in self.world.slithers.iter() {
+            let radius = slither.body.cell_radius(&self.config);
+
+            if !self.config.arena.contains(slither.body.head(), radius) {
+                crashes.insert(id, DeathCause::Wall);
+                continue;
+            }
+
+            let path = slither.body.head_path(&self.config);
 
-            let acceptable_area = Rect::from_min_max(offset.to_pos2(), self.world.size() - offset);
+            if self.config.hits_obstacle(path, radius) {
+                crashes.insert(id, DeathCause::Obstacle);
+                continue;
+            }
 
-            if !acceptable_area.contains(slither.body.head()) {
-                self.crashed.push(id);
+            // a ghost passes through bodies
+            if slither.has(PowerUp::Ghost) {
                 continue;
             }
 
-            for (other_id, other) in self.world.slithers.iter() {
-                if id == other_id {
-                    continue;
-                }
+            // slithers whose heads met touch each other's heads only by the head-on rule,
+            // the rest of their bodies kills as usual
+            let head_of_met = |cell: &BodyCell| {
+                let other_head = self.world.slithers[cell.id].body.head();
+
+                head_ons.contains(&(id.min(cell.id), id.max(cell.id)))
+                    && self.config.arena.delta(other_head, cell.pos).length() < radius + cell.radius
+            };
 
-                if slither.body.crashed_into(&other.body) {
-                    self.crashed.push(id);
-                }
+            if let Some(cell) = bodies.crashed_into(id, &slither.body, &self.config, head_of_met) {
+                crashes.insert(id, DeathCause::Body(cell.id));
             }
         }
 
-        let mut rng = rand::thread_rng();
+        for &(a, b) in &head_ons {
+            for (loser, winner) in self.head_on_losers(a, b) {
+                crashes.entry(loser).or_insert(DeathCause::HeadOn(winner));
+            }
+        }
+
+        for &cause in crashes.values() {
+            if let Some(killer) = cause.killer() {
+                self.world.slithers[killer].kills += 1;
+            }
+        }
 
-        for &id in &self.crashed {
+        for (id, cause) in crashes {
             let slither = self.world.slithers.remove(id);
 
-            self.world.distribute_slither_mass(slither, &mut rng);
+            self.events.push(GameEvent::SlitherDied(Death {
+                id,
+                nickname: slither.nickname.clone(),
+                cause,
+            }));
+
+            self.events.push(GameEvent::MassDropped {
+                id,
+                amount: slither.body.mass(),
+            });
+
+            self.world
+                .distribute_slither_mass(slither, &self.config, &mut self.rng);
+        }
+    }
+
+    /// who dies of a head-on and who kills them
+    fn head_on_losers(&self, a: SlitherID, b: SlitherID) -> Vec<(SlitherID, SlitherID)> {
+        let mass = |id| self.world.slithers[id].body.mass();
+
+        match self.config.head_on {
+            HeadOnRule::BothDie => vec![(a, b), (b, a)],
+
+            HeadOnRule::LargerSurvives => match mass(a).total_cmp(&mass(b)) {
+                Ordering::Less => vec![(a, b)],
+                Ordering::Greater => vec![(b, a)],
+                Ordering::Equal => vec![(a, b), (b, a)],
+            },
+
+            HeadOnRule::NeitherDies => Vec::new(),
+        }
+    }
+}
+
+/// Why a slither died
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// crashed into the world border
+    Wall,
+    /// crashed into one of [`GameConfig::obstacles`]
+    Obstacle,
+    /// crashed into the body of another slither
+    Body(SlitherID),
+    /// met the head of another slither and lost by [`crate::HeadOnRule`]
+    HeadOn(SlitherID),
+}
+
+impl DeathCause {
+    pub fn killer(self) -> Option<SlitherID> {
+        match self {
+            DeathCause::Wall | DeathCause::Obstacle => None,
+            DeathCause::Body(killer) | DeathCause::HeadOn(killer) => Some(killer),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Death {
+    pub id: SlitherID,
+    /// the slither is already removed from the world, so its name is kept here
+    pub nickname: String,
+    pub cause: DeathCause,
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use emath::{Pos2, Vec2};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{DeathCause, GameState};
+    use crate::{
+        Arena, GameConfig, GameEvent, HeadOnRule, Input, Map, MassClot, Obstacle, PowerUp, Slither,
+        SlitherID, World,
+    };
+
+    fn run(seed: u64) -> Vec<Vec<u8>> {
+        let config = GameConfig {
+            seed,
+            arena: Arena::Rect {
+                width: 1000.,
+                height: 1000.,
+            },
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let map = Map {
+            food_mass: 5000.,
+            ..Map::from_config(&config)
+        };
+        let mut state = GameState::from_map(config, map, rng);
+
+        // inputs are generated separately, so they are the same for both runs
+        let mut inputs = StdRng::seed_from_u64(42);
+
+        for id in 0..8 {
+            let pos = Pos2::new(
+                inputs.gen_range(100.0..900.0),
+                inputs.gen_range(100.0..900.0),
+            );
+            let slither = Slither::from_dir(Color32::WHITE, pos, 0., 200., String::new());
+
+            state.world.add_slither(SlitherID(id), slither);
+        }
+
+        let mut snapshots = Vec::new();
+
+        for tick in 0..600 {
+            let delta_time = state.config.delta_time();
+
+            for (_, slither) in state.world.slithers.iter_mut() {
+                slither.set_boost(inputs.gen_bool(0.3), &state.config);
+                slither.change_dir(
+                    inputs.gen_range(0.0..std::f32::consts::TAU),
+                    delta_time,
+                    &state.config,
+                );
+            }
+
+            state.update(delta_time);
+
+            if tick % 60 == 0 {
+                snapshots.push(bincode::serialize(&state.world).unwrap());
+            }
+        }
+
+        snapshots
+    }
+
+    #[test]
+    fn same_seed_same_world() {
+        assert!(run(7) == run(7));
+    }
+
+    #[test]
+    fn different_seeds_different_worlds() {
+        assert!(run(7) != run(8));
+    }
+
+    #[test]
+    fn held_boost_goes_on_when_mass_comes_back() {
+        let config = GameConfig {
+            pickup_spawn_rate: 0.,
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        let id = SlitherID(0);
+        let mass = state.config.min_boost_mass + 1.;
+        let slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(1000., 1000.),
+            0.,
+            mass,
+            String::new(),
+        );
+
+        state.world.add_slither(id, slither);
+        state.set_boost(id, true);
+
+        let delta_time = state.config.delta_time();
+
+        for _ in 0..600 {
+            if !state.world.slithers[id].boost {
+                break;
+            }
+
+            state.update(delta_time);
+        }
+
+        assert!(!state.world.slithers[id].boost);
+
+        let head = state.world.slithers[id].body.head();
+        state
+            .world
+            .spawn_clot(MassClot::new(head, 10., Color32::WHITE));
+
+        // eats during the first update and boosts again during the second one
+        state.update(delta_time);
+        state.update(delta_time);
+
+        assert!(state.world.slithers[id].boost);
+    }
+
+    /// A slither of 1000 mass which has moved from `pos` in `dir` long enough to stretch out,
+    /// 200 ticks at the default config
+    fn long_body(pos: Pos2, dir: f32) -> Slither {
+        let config = GameConfig::default();
+
+        let mut slither = Slither::from_dir(Color32::WHITE, pos, dir, 1000., String::new());
+
+        for _ in 0..200 {
+            slither.body.resize(&config);
+            slither.do_move(1. / 60., &config);
+        }
+
+        slither
+    }
+
+    /// A light slither goes across a heavy one lying on its way,
+    /// returns how the light one died
+    fn cross_body(config: GameConfig, boost: bool, ghost: bool) -> Option<DeathCause> {
+        let world = World::default();
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, world, rng);
+
+        let (victim, attacker) = (SlitherID(0), SlitherID(1));
+
+        let up = -std::f32::consts::FRAC_PI_2;
+        let body = long_body(Pos2::new(1000., 1500.), up);
+
+        let crossing_y = 1250.;
+
+        // the body lies along x = 1000 across the way of the head
+        let cells = body.body.cells();
+        assert!(cells.iter().all(|cell| (cell.x - 1000.).abs() < 1.));
+        assert!(body.body.head().y < crossing_y && body.body.end().y > crossing_y);
+
+        let safe_dist = body.body.cell_radius(&state.config) + 8.;
+
+        state.world.add_slither(victim, body);
+
+        let pos = Pos2::new(1000. - safe_dist - 5., crossing_y);
+        let mut head = Slither::from_dir(Color32::WHITE, pos, 0., 100., String::new());
+
+        if ghost {
+            head.grant(PowerUp::Ghost, 1.);
+        }
+
+        let speed = if boost { 2. } else { 1. } * head.speed(&state.config);
+
+        // the head ends up behind the body
+        assert!(speed * state.config.delta_time() > 2. * safe_dist + 5.);
+
+        state.world.add_slither(attacker, head);
+        state.set_boost(attacker, boost);
+
+        state.update(state.config.delta_time());
+
+        let cause = state.drain_events().find_map(|event| match event {
+            GameEvent::SlitherDied(death) if death.id == attacker => Some(death.cause),
+            _ => None,
+        });
+
+        cause
+    }
+
+    #[test]
+    fn fast_head_doesnt_jump_over_body() {
+        let config = GameConfig {
+            mass_speed_coef: 20000.,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            cross_body(config, false, false),
+            Some(DeathCause::Body(SlitherID(0)))
+        );
+    }
+
+    #[test]
+    fn slow_ticks_dont_let_head_jump_over_body() {
+        let config = GameConfig {
+            tick_rate: 4.,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            cross_body(config, true, false),
+            Some(DeathCause::Body(SlitherID(0)))
+        );
+    }
+
+    #[test]
+    fn ghost_passes_through_bodies() {
+        let config = GameConfig {
+            mass_speed_coef: 20000.,
+            ..Default::default()
+        };
+
+        assert_eq!(cross_body(config, false, true), None);
+    }
+
+    /// A slither with the power-ups for `duration` goes along a line of clots for a second
+    fn eat_line_of_food(power_ups: &[PowerUp], duration: f32) -> (GameState, Vec<GameEvent>) {
+        let config = GameConfig {
+            pickup_spawn_rate: 0.,
+            ..Default::default()
+        };
+
+        let mut state = GameState::new(config, World::default(), GameConfig::default().rng());
+
+        let mut slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(500., 500.),
+            0.,
+            100.,
+            String::new(),
+        );
+
+        for &power_up in power_ups {
+            slither.grant(power_up, duration);
+        }
+
+        state.world.add_slither(A, slither);
+
+        // off the way of the head, so only a magnet gets them
+        for x in 0..10 {
+            let pos = Pos2::new(550. + 20. * x as f32, 560.);
+
+            state
+                .world
+                .spawn_clot(MassClot::new(pos, 10., Color32::WHITE));
+        }
+
+        let mut events = Vec::new();
+
+        for _ in 0..60 {
+            state.update(state.config.delta_time());
+            events.extend(state.drain_events());
         }
+
+        (state, events)
+    }
+
+    fn eaten(events: &[GameEvent]) -> f32 {
+        events
+            .iter()
+            .map(|event| match event {
+                GameEvent::ClotEaten { amount, .. } => *amount,
+                _ => 0.,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn food_drifts_into_the_mouth() {
+        let mut state = GameState::new(
+            GameConfig::default(),
+            World::default(),
+            GameConfig::default().rng(),
+        );
+
+        let slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(500., 500.),
+            0.,
+            100.,
+            String::new(),
+        );
+
+        state.world.add_slither(A, slither);
+
+        // too far from the way of the head to be touched
+        state
+            .world
+            .spawn_clot(MassClot::new(Pos2::new(530., 520.), 10., Color32::WHITE));
+
+        let mut drifted = false;
+        let mut eaten = false;
+
+        for _ in 0..30 {
+            state.update(state.config.delta_time());
+
+            drifted |= state.world.clots.iter().any(|clot| clot.drift.is_some());
+            eaten |= state
+                .drain_events()
+                .any(|event| matches!(event, GameEvent::ClotEaten { .. }));
+        }
+
+        assert!(drifted && eaten);
+    }
+
+    #[test]
+    fn magnet_pulls_food() {
+        let (_, without) = eat_line_of_food(&[], 0.);
+        let (_, with) = eat_line_of_food(&[PowerUp::Magnet], 2.);
+
+        assert_eq!(eaten(&without), 0.);
+        assert!(eaten(&with) > 0.);
+    }
+
+    #[test]
+    fn double_food_doubles_the_mass() {
+        let (state, events) = eat_line_of_food(&[PowerUp::Magnet, PowerUp::DoubleFood], 2.);
+
+        let gained = state.world.slithers[A].body.mass() - 100.;
+
+        assert!(eaten(&events) > 0.);
+        assert!((gained - 2. * eaten(&events)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn power_ups_wear_off() {
+        let (state, events) = eat_line_of_food(&[PowerUp::Speed], 0.5);
+
+        assert!(state.world.slithers[A].power_ups.is_empty());
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::PowerUpEnded {
+                power_up: PowerUp::Speed,
+                ..
+            }
+        )));
+    }
+
+    /// How each of [`A`] and [`B`] died during the last update.
+    /// Checks that a survivor got a kill for each slither it killed
+    fn deaths(state: &mut GameState) -> [Option<DeathCause>; 2] {
+        let mut causes = [None, None];
+
+        for event in state.drain_events() {
+            if let GameEvent::SlitherDied(death) = event {
+                causes[death.id.0 as usize] = Some(death.cause);
+            }
+        }
+
+        for (id, slither) in state.world.slithers.iter() {
+            let killed = causes
+                .iter()
+                .flatten()
+                .filter(|cause| cause.killer() == Some(id))
+                .count();
+
+            assert_eq!(slither.kills as usize, killed, "kills of {id:?}");
+        }
+
+        causes
+    }
+
+    /// Two slithers go head to head, returns which of them died and by what
+    fn head_on(rule: HeadOnRule, masses: [f32; 2]) -> [Option<DeathCause>; 2] {
+        let config = GameConfig {
+            head_on: rule,
+            ..Default::default()
+        };
+
+        let world = World::default();
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, world, rng);
+
+        let radii = masses.map(|mass| {
+            Slither::from_dir(Color32::WHITE, Pos2::ZERO, 0., mass, String::new())
+                .body
+                .cell_radius(&state.config)
+        });
+
+        // the heads are a bit too far to touch, but meet after a move
+        let left = Pos2::new(1000., 1000.);
+        let right = left + Vec2::X * (radii[0] + radii[1] + 2.);
+
+        let slithers = [(left, 0.), (right, std::f32::consts::PI)];
+
+        for (id, ((pos, dir), mass)) in slithers.into_iter().zip(masses).enumerate() {
+            let slither = Slither::from_dir(Color32::WHITE, pos, dir, mass, String::new());
+
+            state.world.add_slither(SlitherID(id as u32), slither);
+        }
+
+        state.update(state.config.delta_time());
+
+        deaths(&mut state)
+    }
+
+    const A: SlitherID = SlitherID(0);
+    const B: SlitherID = SlitherID(1);
+
+    #[test]
+    fn head_on_both_die() {
+        let causes = head_on(HeadOnRule::BothDie, [100., 300.]);
+
+        assert_eq!(
+            causes,
+            [Some(DeathCause::HeadOn(B)), Some(DeathCause::HeadOn(A))]
+        );
+    }
+
+    #[test]
+    fn head_on_larger_survives() {
+        let causes = head_on(HeadOnRule::LargerSurvives, [100., 300.]);
+
+        assert_eq!(causes, [Some(DeathCause::HeadOn(B)), None]);
+    }
+
+    #[test]
+    fn head_on_larger_survives_whatever_the_order() {
+        let causes = head_on(HeadOnRule::LargerSurvives, [300., 100.]);
+
+        assert_eq!(causes, [None, Some(DeathCause::HeadOn(A))]);
+    }
+
+    #[test]
+    fn head_on_of_equals_kills_both() {
+        let causes = head_on(HeadOnRule::LargerSurvives, [200., 200.]);
+
+        assert_eq!(
+            causes,
+            [Some(DeathCause::HeadOn(B)), Some(DeathCause::HeadOn(A))]
+        );
+    }
+
+    #[test]
+    fn head_on_neither_dies() {
+        let causes = head_on(HeadOnRule::NeitherDies, [100., 300.]);
+
+        assert_eq!(causes, [None, None]);
+    }
+
+    /// A crosses the way the head of B has just left, so it hits the body of B
+    fn t_bone(rule: HeadOnRule) -> [Option<DeathCause>; 2] {
+        let config = GameConfig {
+            head_on: rule,
+            mass_speed_coef: 60000.,
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        let up = -std::f32::consts::FRAC_PI_2;
+        let b = long_body(Pos2::new(1000., 1500.), up);
+
+        let crossing = b.body.head();
+        let delta_time = state.config.delta_time();
+
+        let b_step = b.speed(&state.config) * delta_time;
+        let b_radius = b.body.cell_radius(&state.config);
+
+        // the body of B still lies at the crossing after the move
+        assert!(b.body.end().y > crossing.y + b_step + b_radius);
+
+        let probe = Slither::from_dir(Color32::WHITE, crossing, 0., 100., String::new());
+
+        let a_step = probe.speed(&state.config) * delta_time;
+        let safe_dist = probe.body.cell_radius(&state.config) + b_radius;
+
+        // A is in the middle of its way when it crosses, B is already far then
+        let pos = crossing - Vec2::X * a_step / 2.;
+        let a = Slither::from_dir(Color32::WHITE, pos, 0., 100., String::new());
+
+        assert!(a_step / 2. > safe_dist && b_step / 2. > safe_dist);
+
+        state.world.add_slither(A, a);
+        state.world.add_slither(B, b);
+
+        state.update(delta_time);
+
+        deaths(&mut state)
+    }
+
+    #[test]
+    fn t_bone_isnt_head_on() {
+        assert_eq!(
+            t_bone(HeadOnRule::BothDie),
+            [Some(DeathCause::Body(B)), None]
+        );
+    }
+
+    #[test]
+    fn t_bone_kills_even_if_heads_pass_through() {
+        assert_eq!(
+            t_bone(HeadOnRule::NeitherDies),
+            [Some(DeathCause::Body(B)), None]
+        );
+    }
+
+    #[test]
+    fn obstacle_kills() {
+        let config = GameConfig {
+            obstacles: vec![Obstacle::Circle {
+                center: Pos2::new(600., 500.),
+                radius: 50.,
+            }],
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        let slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(500., 500.),
+            0.,
+            100.,
+            String::new(),
+        );
+
+        state.world.add_slither(A, slither);
+
+        // a bystander doesn't get the kill
+        let bystander = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(500., 1500.),
+            0.,
+            100.,
+            String::new(),
+        );
+
+        state.world.add_slither(B, bystander);
+
+        let mut cause = None;
+
+        for _ in 0..60 {
+            state.update(state.config.delta_time());
+
+            cause = cause.or(state.drain_events().find_map(|event| match event {
+                GameEvent::SlitherDied(death) => Some(death.cause),
+                _ => None,
+            }));
+        }
+
+        assert_eq!(cause, Some(DeathCause::Obstacle));
+        assert_eq!(state.world.slithers[B].kills, 0);
+    }
+
+    #[test]
+    fn wall_kills_without_a_killer() {
+        let config = GameConfig::default();
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        let left = std::f32::consts::PI;
+
+        // B is farther, so it's still alive when A dies
+        for (id, pos) in [(A, Pos2::new(50., 500.)), (B, Pos2::new(400., 1500.))] {
+            let slither = Slither::from_dir(Color32::WHITE, pos, left, 100., String::new());
+
+            state.world.add_slither(id, slither);
+        }
+
+        let mut causes = [None, None];
+
+        for _ in 0..150 {
+            state.update(state.config.delta_time());
+
+            for (cause, death) in causes.iter_mut().zip(deaths(&mut state)) {
+                *cause = cause.or(death);
+            }
+        }
+
+        assert_eq!(causes, [Some(DeathCause::Wall), Some(DeathCause::Wall)]);
+    }
+
+    #[test]
+    fn mass_of_crashed_slither_lands_off_obstacles() {
+        let config = GameConfig {
+            obstacles: vec![Obstacle::Circle {
+                center: Pos2::new(600., 500.),
+                radius: 50.,
+            }],
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        // the head is wide, so the clots around it would reach into the obstacle
+        let slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(500., 500.),
+            0.,
+            3000.,
+            String::new(),
+        );
+
+        state.world.add_slither(A, slither);
+
+        for _ in 0..60 {
+            if !state.world.slithers.exists(A) {
+                break;
+            }
+
+            state.update(state.config.delta_time());
+        }
+
+        assert!(!state.world.slithers.exists(A));
+        assert!(!state.world.clots.is_empty());
+
+        for clot in state.world.clots.iter() {
+            assert!(state.config.is_free(clot.pos, clot.radius()));
+        }
+    }
+
+    fn torus_state() -> GameState {
+        let config = GameConfig {
+            arena: Arena::Torus {
+                width: 1000.,
+                height: 1000.,
+            },
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+
+        GameState::new(config, World::default(), rng)
+    }
+
+    #[test]
+    fn torus_edge_leads_to_the_opposite_one() {
+        let mut state = torus_state();
+
+        let slither = Slither::from_dir(
+            Color32::WHITE,
+            Pos2::new(990., 500.),
+            0.,
+            200.,
+            String::new(),
+        );
+
+        state.world.add_slither(A, slither);
+
+        for _ in 0..60 {
+            state.update(state.config.delta_time());
+        }
+
+        assert!(state.world.slithers.exists(A));
+
+        let body = &state.world.slithers[A].body;
+        let spacing = body.cells_dist(&state.config);
+
+        assert!(body.head().x < 500.);
+
+        for pair in body.cells().windows(2) {
+            let dist = state.config.arena.distance(pair[0], pair[1]);
+
+            assert!((dist - spacing).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn torus_body_kills_across_the_edge() {
+        let mut state = torus_state();
+
+        let up = -std::f32::consts::FRAC_PI_2;
+        let body = long_body(Pos2::new(10., 700.), up);
+
+        let crossing_y = 450.;
+
+        // the body lies along x = 10 across the way of the head, away from the edges
+        let cells = body.body.cells();
+        assert!(cells.iter().all(|cell| (cell.x - 10.).abs() < 1.));
+        assert!(body.body.head().y < crossing_y && body.body.end().y > crossing_y);
+
+        let radii = body.body.cell_radius(&state.config) + 8.;
+
+        state.world.add_slither(A, body);
+
+        let pos = Pos2::new(1000. - radii - 5., crossing_y);
+        let head = Slither::from_dir(Color32::WHITE, pos, 0., 100., String::new());
+
+        state.world.add_slither(B, head);
+
+        let mut cause = None;
+
+        for _ in 0..30 {
+            state.update(state.config.delta_time());
+
+            cause = cause.or(state.drain_events().find_map(|event| match event {
+                GameEvent::SlitherDied(death) if death.id == B => Some(death.cause),
+                _ => None,
+            }));
+        }
+
+        assert_eq!(cause, Some(DeathCause::Body(A)));
+    }
+
+    #[test]
+    fn restored_state_goes_on_the_same() {
+        let config = GameConfig {
+            arena: Arena::Rect {
+                width: 1000.,
+                height: 1000.,
+            },
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let map = Map {
+            food_mass: 5000.,
+            ..Map::from_config(&config)
+        };
+        let mut state = GameState::from_map(config.clone(), map, rng);
+
+        let mut inputs = StdRng::seed_from_u64(42);
+
+        for id in 0..8 {
+            state.apply(Input::Join {
+                id: SlitherID(id),
+                color: Color32::WHITE,
+                nickname: String::new(),
+            });
+        }
+
+        let mut random_inputs = |tick: u32| -> Vec<Input> {
+            let id = SlitherID(tick % 8);
+
+            vec![
+                Input::Direction(id, inputs.gen_range(0.0..std::f32::consts::TAU)),
+                Input::Boost(id, inputs.gen_bool(0.3)),
+            ]
+        };
+
+        let update = |state: &mut GameState, inputs: &[Input]| {
+            for input in inputs {
+                state.apply(input.clone());
+            }
+
+            state.update(state.config.delta_time());
+        };
+
+        for tick in 0..300 {
+            update(&mut state, &random_inputs(tick));
+        }
+
+        let mut restored: GameState =
+            bincode::deserialize(&bincode::serialize(&state).unwrap()).unwrap();
+        restored.config = config;
+
+        for tick in 300..600 {
+            let inputs = random_inputs(tick);
+
+            update(&mut state, &inputs);
+            update(&mut restored, &inputs);
+        }
+
+        assert!(bincode::serialize(&state).unwrap() == bincode::serialize(&restored).unwrap());
     }
 }
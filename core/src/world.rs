@@ -1,5 +1,13 @@
+mod arena;
+mod body_index;
+mod food_spawner;
+mod ledger;
+mod map;
 mod mass_clots;
+mod obstacle;
 mod slithers;
+pub(crate) mod snapshot;
+mod spawn;
 
 use std::f32::consts::PI;
 
@@ -8,68 +16,147 @@ use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use slithers::Slithers;
 
-pub const MIN_CLOT_MASS: f32 = 10.;
-pub const MAX_CLOT_MASS: f32 = 25.;
-
+pub use arena::Arena;
+pub use body_index::{BodyCell, BodyIndex};
+pub use food_spawner::FoodSpawner;
+pub use ledger::MassLedger;
+pub use map::{FoodZone, Map, Region};
 pub use mass_clots::{MassClot, MassClots};
+pub use obstacle::Obstacle;
 pub use slithers::SlitherID;
 
-use crate::Slither;
+use crate::{GameConfig, Pickup, Segment, Slither};
+
+/// How many random places are tried to find one outside the obstacles
+const FREE_POS_TRIES: u32 = 32;
 
+/// What changes while the game goes, it's sent to the clients every tick.
+/// The arena and the obstacles don't change, so they are kept in the config
 #[derive(Default, Serialize, Deserialize)]
 pub struct World {
     pub slithers: Slithers,
     pub clots: MassClots,
-
-    pub width: f32,
-    pub height: f32,
+    pub pickups: Vec<Pickup>,
+    /// bookkeeping of the server, clients don't get it
+    #[serde(skip)]
+    pub ledger: MassLedger,
 }
 
 impl World {
-    pub fn new(width: f32, height: f32, mass: f32) -> Self {
-        Self {
-            slithers: Slithers::default(),
-            clots: MassClots::new(width, height, mass),
+    /// adds a slither with the mass it brings
+    pub fn add_slither(&mut self, id: SlitherID, slither: Slither) {
+        self.ledger.joined += slither.body.mass() as f64;
 
-            width,
-            height,
-        }
+        self.slithers.add(id, slither);
     }
 
-    pub fn distribute_slither_mass<R: Rng>(&mut self, slither: Slither, rng: &mut R) {
+    /// adds a clot of new mass
+    pub fn spawn_clot(&mut self, clot: MassClot) {
+        self.ledger.spawned += clot.amount as f64;
+
+        self.clots.add(clot);
+    }
+
+    /// Scatters the mass of the slither around its body as clots.
+    /// Clots which would land on an obstacle are moved away from it
+    pub fn distribute_slither_mass<R: Rng>(
+        &mut self,
+        slither: Slither,
+        config: &GameConfig,
+        rng: &mut R,
+    ) {
         let mut mass = slither.body.mass();
 
-        let generate_clot = |rng: &mut R, amount| {
+        self.ledger.dropped += mass as f64;
+
+        let near_body = |rng: &mut R| {
             let &pos = slither.body.cells().choose(rng).unwrap();
 
-            let radius = rng.gen_range(0.0..slither.body.cell_radius());
+            let radius = rng.gen_range(0.0..slither.body.cell_radius(config));
             let angle = rng.gen_range(0.0..2. * PI);
 
-            let pos = pos + Vec2::angled(angle) * radius;
+            pos + Vec2::angled(angle) * radius
+        };
+
+        let new_clot = |rng: &mut R, amount: f32| {
+            let radius = amount.sqrt();
+
+            let mut pos = config.free_pos(rng, radius, &near_body);
 
-            let color = slither.color;
+            // the body may lie along an obstacle, then the clot goes anywhere else
+            if !config.is_free(pos, radius) {
+                pos = config.random_pos(rng, radius);
+            }
 
-            MassClot { pos, amount, color }
+            MassClot::new(pos, amount, slither.color)
         };
 
-        while mass > MIN_CLOT_MASS {
-            let amount = rng.gen_range(MIN_CLOT_MASS..MAX_CLOT_MASS);
+        while mass > config.min_clot_mass {
+            let amount = rng
+                .gen_range(config.min_clot_mass..config.max_clot_mass)
+                .min(mass);
 
-            let clot = generate_clot(rng, amount);
+            self.clots.add(new_clot(rng, amount));
 
-            self.clots.add(clot);
+            mass -= amount;
+        }
+
+        if mass > 0. {
+            self.clots.add(new_clot(rng, mass));
+        }
+    }
+
+    /// total mass of the slithers and the clots
+    pub fn mass(&self) -> f32 {
+        let slithers_mass: f32 = self
+            .slithers
+            .iter()
+            .map(|(_, slither)| slither.body.mass())
+            .sum();
+
+        slithers_mass + self.clots.mass()
+    }
+}
+
+/// Placement in the arena of the config, around its obstacles
+impl GameConfig {
+    /// a random point outside the obstacles, at least `margin` away from them if possible
+    pub fn random_pos(&self, rng: &mut impl Rng, margin: f32) -> Pos2 {
+        self.free_pos(rng, margin, |rng| self.arena.random_pos(rng))
+    }
 
-            mass -= clot.amount;
+    /// A point of `random_pos` at least `margin` away from the obstacles.
+    /// If there is no such point among the tried, the last one is taken
+    pub fn free_pos<R: Rng>(
+        &self,
+        rng: &mut R,
+        margin: f32,
+        mut random_pos: impl FnMut(&mut R) -> Pos2,
+    ) -> Pos2 {
+        let mut pos = self.arena.wrap(random_pos(rng));
+
+        for _ in 1..FREE_POS_TRIES {
+            if self.is_free(pos, margin) {
+                break;
+            }
+
+            pos = self.arena.wrap(random_pos(rng));
         }
 
-        self.clots.add(generate_clot(rng, mass));
+        pos
     }
 
-    pub fn size(&self) -> Pos2 {
-        Pos2::new(self.width, self.height)
+    /// whether the point is at least `margin` away from all the obstacles
+    pub fn is_free(&self, pos: Pos2, margin: f32) -> bool {
+        self.obstacles
+            .iter()
+            .all(|obstacle| obstacle.distance(pos, &self.arena) >= margin)
     }
 
-    pub fn center(&self) -> Pos2 {
-        self.size() * 0.5
+    /// whether a circle of `radius` touches an obstacle anywhere on its way
+    pub fn hits_obstacle(&self, path: Segment, radius: f32) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.touches(path, radius, &self.arena))
     }
 }
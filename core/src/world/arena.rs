@@ -0,0 +1,282 @@
+use std::f32::consts::PI;
+
+use emath::{Pos2, Vec2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::Segment;
+
+/// The playable area, slithers die on its border. It lies in the rectangle from zero to [`Arena::size`]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arena {
+    Rect {
+        width: f32,
+        height: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+    /// a rectangle without walls, leaving it on one side is entering it on the opposite one
+    Torus {
+        width: f32,
+        height: f32,
+    },
+}
+
+impl Arena {
+    /// Checks the sides or the radius are positive numbers
+    pub fn validate(&self) -> Result<(), String> {
+        let sizes = match *self {
+            Arena::Rect { width, height } | Arena::Torus { width, height } => {
+                vec![("width", width), ("height", height)]
+            }
+
+            Arena::Circle { radius } => vec![("radius", radius)],
+        };
+
+        for (name, size) in sizes {
+            if !size.is_finite() || size <= 0. {
+                return Err(format!("{name} of the arena must be positive, got {size}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the corner of the bounding rectangle opposite to zero
+    pub fn size(&self) -> Pos2 {
+        match *self {
+            Arena::Rect { width, height } | Arena::Torus { width, height } => {
+                Pos2::new(width, height)
+            }
+            Arena::Circle { radius } => Pos2::new(2. * radius, 2. * radius),
+        }
+    }
+
+    pub fn center(&self) -> Pos2 {
+        self.size() * 0.5
+    }
+
+    pub fn area(&self) -> f32 {
+        match *self {
+            Arena::Rect { width, height } | Arena::Torus { width, height } => width * height,
+            Arena::Circle { radius } => PI * radius * radius,
+        }
+    }
+
+    /// distance from the point to the border, negative outside, infinite if there is no border
+    pub fn depth(&self, pos: Pos2) -> f32 {
+        match *self {
+            Arena::Rect { width, height } => [pos.x, pos.y, width - pos.x, height - pos.y]
+                .into_iter()
+                .fold(f32::INFINITY, f32::min),
+
+            Arena::Circle { radius } => radius - pos.distance(self.center()),
+
+            Arena::Torus { .. } => f32::INFINITY,
+        }
+    }
+
+    /// whether the point is inside at least `margin` away from the border
+    pub fn contains(&self, pos: Pos2, margin: f32) -> bool {
+        self.depth(pos) >= margin
+    }
+
+    /// vectors from the point to the nearest points of each wall
+    pub fn walls(&self, pos: Pos2) -> Vec<Vec2> {
+        match *self {
+            Arena::Rect { width, height } => vec![
+                Vec2::new(-pos.x, 0.),
+                Vec2::new(0., -pos.y),
+                Vec2::new(width - pos.x, 0.),
+                Vec2::new(0., height - pos.y),
+            ],
+
+            Arena::Circle { radius } => {
+                let from_center = pos - self.center();
+
+                if from_center == Vec2::ZERO {
+                    return Vec::new();
+                }
+
+                vec![from_center.normalized() * (radius - from_center.length())]
+            }
+
+            Arena::Torus { .. } => Vec::new(),
+        }
+    }
+
+    /// uniformly distributed over the area
+    pub fn random_pos(&self, rng: &mut impl Rng) -> Pos2 {
+        match *self {
+            Arena::Rect { width, height } | Arena::Torus { width, height } => {
+                Pos2::new(rng.gen_range(0.0..width), rng.gen_range(0.0..height))
+            }
+
+            Arena::Circle { radius } => {
+                // sqrt makes points spread evenly over the circle area
+                let distance = radius * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..2. * PI);
+
+                self.center() + Vec2::angled(angle) * distance
+            }
+        }
+    }
+
+    /// the nearest point inside
+    pub fn clamp(&self, pos: Pos2) -> Pos2 {
+        match *self {
+            Arena::Rect { .. } => pos.clamp(Pos2::ZERO, self.size()),
+
+            Arena::Circle { radius } => {
+                let center = self.center();
+                let from_center = pos - center;
+
+                if from_center.length() <= radius {
+                    pos
+                } else {
+                    center + from_center.normalized() * radius
+                }
+            }
+
+            Arena::Torus { .. } => self.wrap(pos),
+        }
+    }
+
+    pub fn wraps(&self) -> bool {
+        matches!(self, Arena::Torus { .. })
+    }
+
+    /// the same point inside the arena if it wraps, otherwise the point itself
+    pub fn wrap(&self, pos: Pos2) -> Pos2 {
+        match *self {
+            Arena::Torus { width, height } => {
+                Pos2::new(pos.x.rem_euclid(width), pos.y.rem_euclid(height))
+            }
+
+            _ => pos,
+        }
+    }
+
+    /// the shortest way from one point to another, it may go across the edges if the arena wraps
+    pub fn delta(&self, from: Pos2, to: Pos2) -> Vec2 {
+        let delta = to - from;
+
+        match *self {
+            Arena::Torus { width, height } => Vec2::new(
+                delta.x - width * (delta.x / width).round(),
+                delta.y - height * (delta.y / height).round(),
+            ),
+
+            _ => delta,
+        }
+    }
+
+    pub fn distance(&self, a: Pos2, b: Pos2) -> f32 {
+        self.delta(a, b).length()
+    }
+
+    /// the copy of the point nearest to another one, the point itself if the arena doesn't wrap
+    pub fn copy_near(&self, pos: Pos2, to: Pos2) -> Pos2 {
+        to + self.delta(to, pos)
+    }
+
+    /// the copy of the segment nearest to the point, the segment itself if the arena doesn't wrap
+    pub fn align(&self, segment: Segment, to: Pos2) -> Segment {
+        let shift = self.delta(to, segment.start) - (segment.start - to);
+
+        Segment::new(segment.start + shift, segment.end + shift)
+    }
+
+    /// Copies of the point shifted by the arena size, whose surroundings of `radius` overlap the arena.
+    /// Things near the point are found near them. It's only the point itself if the arena doesn't wrap
+    pub fn images(&self, pos: Pos2, radius: f32) -> Vec<Pos2> {
+        let Arena::Torus { width, height } = *self else {
+            return vec![pos];
+        };
+
+        let overlaps = |at: f32, size: f32| at + radius >= 0. && at - radius <= size;
+
+        let mut images = Vec::new();
+
+        for dx in [-width, 0., width] {
+            for dy in [-height, 0., height] {
+                let image = pos + Vec2::new(dx, dy);
+
+                if overlaps(image.x, width) && overlaps(image.y, height) {
+                    images.push(image);
+                }
+            }
+        }
+
+        images
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena::Rect {
+            width: 2000.,
+            height: 2000.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::{Pos2, Vec2};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::Arena;
+
+    const CIRCLE: Arena = Arena::Circle { radius: 100. };
+
+    #[test]
+    fn circle_contains_points_away_from_the_border() {
+        let center = CIRCLE.center();
+
+        assert_eq!(center, Pos2::new(100., 100.));
+
+        assert!(CIRCLE.contains(center, 99.));
+        assert!(!CIRCLE.contains(center, 101.));
+
+        assert!(CIRCLE.contains(center + Vec2::X * 90., 5.));
+        assert!(!CIRCLE.contains(center + Vec2::X * 90., 15.));
+
+        // corners of the bounding rectangle are outside
+        assert!(!CIRCLE.contains(Pos2::new(5., 5.), 0.));
+    }
+
+    #[test]
+    fn circle_depth() {
+        let center = CIRCLE.center();
+
+        assert_eq!(CIRCLE.depth(center), 100.);
+        assert_eq!(CIRCLE.depth(center + Vec2::Y * 30.), 70.);
+        assert_eq!(CIRCLE.depth(center - Vec2::X * 130.), -30.);
+    }
+
+    #[test]
+    fn circle_clamp() {
+        let center = CIRCLE.center();
+
+        let inside = center + Vec2::new(20., -40.);
+        assert_eq!(CIRCLE.clamp(inside), inside);
+
+        let clamped = CIRCLE.clamp(center + Vec2::new(300., 400.));
+        assert!((clamped - (center + Vec2::new(60., 80.))).length() < 1e-3);
+    }
+
+    #[test]
+    fn circle_random_pos_stays_inside() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let pos = CIRCLE.random_pos(&mut rng);
+
+            assert!(pos.distance(CIRCLE.center()) <= 100.);
+        }
+    }
+}
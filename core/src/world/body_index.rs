@@ -0,0 +1,248 @@
+use emath::Pos2;
+
+use crate::grid::Grid;
+use crate::{GameConfig, Segment, SlitherBody};
+
+use super::{Arena, SlitherID, Slithers};
+
+/// Side of a square the body cells are bucketed by
+const GRID_CELL_SIZE: f32 = 64.;
+
+/// Broad-phase index over the cells of all the slither bodies.
+/// It's a snapshot, so it has to be rebuilt after the slithers moved
+pub struct BodyIndex {
+    grid: Grid<BodyCell>,
+    /// the farthest a body part reaches from its cell
+    max_reach: f32,
+
+    heads: Grid<HeadPath>,
+    /// the farthest a head path reaches from its center
+    max_head_reach: f32,
+
+    arena: Arena,
+}
+
+#[derive(Clone, Copy)]
+struct HeadPath {
+    id: SlitherID,
+    path: Segment,
+    radius: f32,
+}
+
+/// A cell with the part of the body up to the next cell.
+/// The next cell may be outside a wrapping arena, so the part is never split by the edge
+#[derive(Clone, Copy)]
+pub struct BodyCell {
+    pub id: SlitherID,
+    /// position of the cell in the body, the head is 0
+    pub index: usize,
+    pub pos: Pos2,
+    /// the next cell toward the tail, or the cell itself if it's the last one
+    pub next: Pos2,
+    pub radius: f32,
+}
+
+impl BodyCell {
+    pub fn segment(&self) -> Segment {
+        Segment::new(self.pos, self.next)
+    }
+}
+
+impl BodyIndex {
+    pub fn new(slithers: &Slithers, config: &GameConfig) -> Self {
+        let mut grid = Grid::new(GRID_CELL_SIZE);
+        let mut max_reach = 0f32;
+
+        let mut heads = Grid::new(GRID_CELL_SIZE);
+        let mut max_head_reach = 0f32;
+
+        for (id, slither) in slithers.iter() {
+            let radius = slither.body.cell_radius(config);
+            let path = slither.body.head_path(config);
+
+            max_head_reach = max_head_reach.max(radius + path.length() / 2.);
+
+            heads.insert(path.center(), HeadPath { id, path, radius });
+
+            for (index, segment) in slither.body.segments(config) {
+                max_reach = max_reach.max(radius + segment.length());
+
+                grid.insert(
+                    segment.start,
+                    BodyCell {
+                        id,
+                        index,
+                        pos: segment.start,
+                        next: segment.end,
+                        radius,
+                    },
+                );
+            }
+        }
+
+        Self {
+            grid,
+            max_reach,
+            heads,
+            max_head_reach,
+            arena: config.arena,
+        }
+    }
+
+    /// Cells whose body parts can intersect the circle, some of them may be farther.
+    /// In a wrapping arena they are found across the edges too, so some may come twice
+    pub fn near(&self, center: Pos2, radius: f32) -> impl Iterator<Item = BodyCell> + '_ {
+        let reach = radius + self.max_reach;
+
+        self.arena
+            .images(center, reach)
+            .into_iter()
+            .flat_map(move |center| self.grid.near(center, reach).copied())
+    }
+
+    /// Returns a cell of another slither the head of the body crashed into on its way.
+    /// Finds something if and only if [`SlitherBody::crashed_into`] is true
+    /// for some other slither, not counting ignored cells
+    pub fn crashed_into(
+        &self,
+        id: SlitherID,
+        body: &SlitherBody,
+        config: &GameConfig,
+        ignored: impl Fn(&BodyCell) -> bool,
+    ) -> Option<BodyCell> {
+        let path = body.head_path(config);
+        let radius = body.cell_radius(config);
+
+        self.near(path.center(), radius + path.length() / 2.)
+            .find(|cell| {
+                let safe_dist = radius + cell.radius;
+                let segment = self.arena.align(cell.segment(), path.end);
+
+                cell.id != id && !ignored(cell) && path.distance_sq(&segment) < safe_dist.powi(2)
+            })
+    }
+
+    /// Other slithers whose heads touched the head of the body during the last move.
+    /// The heads have to be close at the same moment, crossing the way another head has left isn't meeting it
+    pub fn met_heads(
+        &self,
+        id: SlitherID,
+        body: &SlitherBody,
+        config: &GameConfig,
+    ) -> impl Iterator<Item = SlitherID> + '_ {
+        let path = body.head_path(config);
+        let radius = body.cell_radius(config);
+
+        let reach = radius + path.length() / 2. + self.max_head_reach;
+
+        self.arena
+            .images(path.center(), reach)
+            .into_iter()
+            .flat_map(move |center| self.heads.near(center, reach))
+            .filter(move |head| {
+                let safe_dist = radius + head.radius;
+                let other_path = self.arena.align(head.path, path.end);
+
+                head.id != id && path.approach_sq(&other_path) < safe_dist.powi(2)
+            })
+            .map(|head| head.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use emath::Pos2;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::BodyIndex;
+    use crate::world::{Arena, SlitherID, Slithers};
+    use crate::{GameConfig, Slither};
+
+    fn random_slithers(rng: &mut impl Rng, config: &GameConfig, count: u32, size: f32) -> Slithers {
+        let mut slithers = Slithers::default();
+
+        for id in 0..count {
+            let pos = Pos2::new(rng.gen_range(0.0..size), rng.gen_range(0.0..size));
+            let pos = config.arena.wrap(pos);
+            let dir = rng.gen_range(0.0..std::f32::consts::TAU);
+            let mass = rng.gen_range(50.0..5000.0);
+
+            let mut slither = Slither::from_dir(Color32::WHITE, pos, dir, mass, String::new());
+
+            for _ in 0..rng.gen_range(0..60) {
+                slither.body.resize(config);
+                slither.do_move(1. / 60., config);
+            }
+
+            slithers.add(SlitherID(id), slither);
+        }
+
+        slithers
+    }
+
+    fn same_as_brute_force(config: GameConfig) {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut crashes = 0;
+
+        for _ in 0..50 {
+            let slithers = random_slithers(&mut rng, &config, 30, 600.);
+            let index = BodyIndex::new(&slithers, &config);
+
+            for (id, slither) in slithers.iter() {
+                let brute_force = slithers.iter().any(|(other_id, other)| {
+                    id != other_id && slither.body.crashed_into(&other.body, &config)
+                });
+
+                let found = index.crashed_into(id, &slither.body, &config, |_| false);
+
+                assert_eq!(found.is_some(), brute_force);
+
+                if let Some(cell) = found {
+                    assert!(slither.body.crashed_into(&slithers[cell.id].body, &config));
+
+                    crashes += 1;
+                }
+            }
+        }
+
+        assert!(crashes > 0);
+    }
+
+    #[test]
+    fn same_as_brute_force_in_rect() {
+        same_as_brute_force(GameConfig::default());
+    }
+
+    #[test]
+    fn same_as_brute_force_across_torus_edges() {
+        same_as_brute_force(GameConfig {
+            arena: Arena::Torus {
+                width: 600.,
+                height: 600.,
+            },
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn ignores_own_body() {
+        let config = GameConfig::default();
+        let mut slithers = Slithers::default();
+
+        let mut slither = Slither::from_dir(Color32::WHITE, Pos2::ZERO, 0., 1000., String::new());
+        slither.body.resize(&config);
+
+        slithers.add(SlitherID(0), slither);
+
+        let index = BodyIndex::new(&slithers, &config);
+
+        assert!(index
+            .crashed_into(SlitherID(0), &slithers[SlitherID(0)].body, &config, |_| {
+                false
+            })
+            .is_none());
+    }
+}
@@ -0,0 +1,261 @@
+use std::f32::consts::PI;
+
+use ecolor::Color32;
+use emath::{Pos2, Vec2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{map, FoodZone, MassClot, World};
+use crate::{FoodDistribution, GameConfig};
+
+/// Tops the world up with food until its total mass reaches `food_mass`
+#[derive(Default, Serialize, Deserialize)]
+pub struct FoodSpawner {
+    /// the world is topped up with food to this mass
+    food_mass: f32,
+    /// where food appears, anywhere in the arena if there are no zones
+    zones: Vec<FoodZone>,
+    /// mass which is allowed to be spawned, but isn't spawned yet
+    budget: f32,
+    cluster: Option<Cluster>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Cluster {
+    center: Pos2,
+    /// the food zone of the cluster, its clots take the colors of it
+    zone: Option<usize>,
+    clots_left: u32,
+}
+
+impl FoodSpawner {
+    pub fn new(food_mass: f32, zones: Vec<FoodZone>) -> Self {
+        Self {
+            food_mass,
+            zones,
+            ..Default::default()
+        }
+    }
+
+    /// spreads all the food over the zones of an empty world
+    pub fn fill(&self, world: &mut World, config: &GameConfig, rng: &mut impl Rng) {
+        let mut mass = self.food_mass;
+
+        while mass > config.min_clot_mass {
+            let amount = rng.gen_range(config.min_clot_mass..config.max_clot_mass);
+
+            mass -= amount;
+
+            let zone = self.random_zone(rng);
+            let clot = self.random_clot(rng, zone, amount, config);
+
+            world.spawn_clot(clot);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        config: &GameConfig,
+        rng: &mut impl Rng,
+        delta_time: f32,
+    ) {
+        let lack = self.food_mass - world.mass();
+
+        if lack <= 0. {
+            self.budget = 0.;
+            return;
+        }
+
+        self.budget = (self.budget + config.food_spawn_rate * delta_time).min(lack);
+
+        while self.budget >= config.min_clot_mass {
+            let amount = rng
+                .gen_range(config.min_clot_mass..config.max_clot_mass)
+                .min(self.budget);
+
+            let clot = self.next_clot(config, amount, rng);
+
+            world.spawn_clot(clot);
+
+            self.budget -= amount;
+        }
+    }
+
+    /// a clot of new food in one of the food zones, never on an obstacle
+    fn next_clot(&mut self, config: &GameConfig, amount: f32, rng: &mut impl Rng) -> MassClot {
+        match config.food_distribution {
+            FoodDistribution::Uniform => {
+                let zone = self.random_zone(rng);
+
+                self.random_clot(rng, zone, amount, config)
+            }
+
+            FoodDistribution::Clustered {
+                radius: cluster_radius,
+                size,
+            } => {
+                let mut cluster = match self.cluster {
+                    Some(cluster) if cluster.clots_left > 0 => cluster,
+
+                    _ => {
+                        let zone = self.random_zone(rng);
+
+                        Cluster {
+                            center: self.random_clot(rng, zone, amount, config).pos,
+                            zone,
+                            clots_left: size,
+                        }
+                    }
+                };
+
+                cluster.clots_left -= 1;
+                self.cluster = Some(cluster);
+
+                // sqrt makes clots spread evenly over the circle area
+                let distance = cluster_radius * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..2. * PI);
+
+                let pos = cluster.center + Vec2::angled(angle) * distance;
+
+                // a cluster at the edge of its zone doesn't spill out of it
+                let pos = match cluster.zone {
+                    Some(zone) => self.zones[zone].region.clamp(pos),
+                    None => pos,
+                };
+
+                let pos = config.arena.clamp(pos);
+
+                // the clot goes elsewhere in the zone if this place is taken by an obstacle
+                if config.is_free(pos, amount.sqrt()) {
+                    MassClot::new(pos, amount, self.color(rng, cluster.zone))
+                } else {
+                    self.random_clot(rng, cluster.zone, amount, config)
+                }
+            }
+        }
+    }
+
+    /// Index of a random food zone, zones with more food are chosen more often.
+    /// `None` means the whole arena
+    fn random_zone(&self, rng: &mut impl Rng) -> Option<usize> {
+        let weights = self
+            .zones
+            .iter()
+            .map(|zone| zone.density * zone.region.area());
+
+        map::choose_weighted(rng, weights)
+    }
+
+    /// a clot of new food in the zone, away from the obstacles
+    fn random_clot(
+        &self,
+        rng: &mut impl Rng,
+        zone: Option<usize>,
+        amount: f32,
+        config: &GameConfig,
+    ) -> MassClot {
+        let radius = amount.sqrt();
+
+        let pos = match zone {
+            Some(zone) => {
+                let region = self.zones[zone].region;
+
+                config.free_pos(rng, radius, |rng| region.random_pos(rng))
+            }
+
+            None => config.random_pos(rng, radius),
+        };
+
+        MassClot::new(pos, amount, self.color(rng, zone))
+    }
+
+    /// a color from the palette of the zone
+    fn color(&self, rng: &mut impl Rng, zone: Option<usize>) -> Color32 {
+        match zone {
+            Some(zone) => self.zones[zone].random_color(rng),
+            None => MassClot::random_color(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FoodSpawner;
+    use crate::{FoodDistribution, GameConfig, World};
+
+    #[test]
+    fn world_is_topped_up_to_the_food_mass() {
+        let config = GameConfig::default();
+        let mut rng = config.rng();
+
+        let mut spawner = FoodSpawner::new(1000., Vec::new());
+        let mut world = World::default();
+
+        for _ in 0..20 {
+            spawner.update(&mut world, &config, &mut rng, 1.);
+        }
+
+        assert!(world.mass() <= 1000.);
+        assert!(world.mass() > 1000. - config.min_clot_mass);
+
+        // eaten food is replaced
+        world.clots = Default::default();
+        spawner.update(&mut world, &config, &mut rng, 1.);
+
+        assert!(world.mass() > 0.);
+    }
+
+    #[test]
+    fn spawn_rate_caps_each_tick() {
+        let config = GameConfig::default();
+        let mut rng = config.rng();
+
+        let mut spawner = FoodSpawner::new(1000., Vec::new());
+        let mut world = World::default();
+
+        let delta_time = config.delta_time();
+        let ticks = 30;
+
+        for tick in 1..=ticks {
+            spawner.update(&mut world, &config, &mut rng, delta_time);
+
+            assert!(world.mass() <= config.food_spawn_rate * delta_time * tick as f32 + 1e-3);
+        }
+
+        let allowed = config.food_spawn_rate * delta_time * ticks as f32;
+
+        assert!(world.mass() > allowed - config.min_clot_mass);
+    }
+
+    #[test]
+    fn clusters_keep_together() {
+        let config = GameConfig {
+            min_clot_mass: 10.,
+            max_clot_mass: 10.01,
+            // four clots and a bit
+            food_spawn_rate: 45.,
+            food_distribution: FoodDistribution::Clustered {
+                radius: 30.,
+                size: 4,
+            },
+            ..Default::default()
+        };
+        let mut rng = config.rng();
+
+        let mut spawner = FoodSpawner::new(1000., Vec::new());
+        let mut world = World::default();
+
+        spawner.update(&mut world, &config, &mut rng, 1.);
+
+        let clots: Vec<_> = world.clots.iter().collect();
+
+        assert_eq!(clots.len(), 4);
+
+        for a in &clots {
+            for b in &clots {
+                assert!(a.pos.distance(b.pos) <= 60.);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use super::World;
+
+/// How far the world mass may drift from the ledger because of rounding, relative to the total
+const TOLERANCE: f64 = 1e-3;
+
+/// Where the mass of the world came from and where it went.
+/// Only spawned food, joined slithers and food eaten with [`crate::PowerUp::DoubleFood`] bring new mass,
+/// the rest moves it between slithers and clots
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct MassLedger {
+    /// source: food the world was created with or spawned later
+    pub spawned: f64,
+    /// source: initial mass of joined slithers
+    pub joined: f64,
+    /// source: extra mass of food eaten with double food
+    pub bonus: f64,
+
+    /// clots to slithers: eaten clots
+    pub eaten: f64,
+    /// slithers to clots: burned while boosting
+    pub burned: f64,
+    /// slithers to clots: bodies scattered after death or leaving
+    pub dropped: f64,
+}
+
+impl MassLedger {
+    /// all the mass ever brought into the world
+    pub fn total(&self) -> f64 {
+        self.spawned + self.joined + self.bonus
+    }
+
+    pub fn clots(&self) -> f64 {
+        self.spawned + self.burned + self.dropped - self.eaten
+    }
+
+    pub fn slithers(&self) -> f64 {
+        self.joined + self.bonus + self.eaten - self.burned - self.dropped
+    }
+
+    /// whether the slithers and the clots weigh as much as the ledger says
+    pub fn balances(&self, world: &World) -> bool {
+        let slithers: f64 = world
+            .slithers
+            .iter()
+            .map(|(_, slither)| slither.body.mass() as f64)
+            .sum();
+
+        let clots = world.clots.mass() as f64;
+
+        let tolerance = TOLERANCE * self.total().max(1.);
+
+        (slithers - self.slithers()).abs() <= tolerance && (clots - self.clots()).abs() <= tolerance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use crate::{Arena, GameConfig, GameState, Input, Map, SlitherID};
+
+    #[derive(Clone, Debug)]
+    enum Step {
+        Input(Input),
+        Update,
+    }
+
+    fn step() -> impl Strategy<Value = Step> {
+        let id = (0..6u32).prop_map(SlitherID);
+
+        prop_oneof![
+            1 => id.clone().prop_map(|id| Step::Input(Input::Join {
+                id,
+                color: Color32::WHITE,
+                nickname: String::new(),
+            })),
+            1 => id.clone().prop_map(|id| Step::Input(Input::Leave(id))),
+            3 => (id.clone(), 0.0..std::f32::consts::TAU)
+                .prop_map(|(id, dir)| Step::Input(Input::Direction(id, dir))),
+            2 => (id, any::<bool>()).prop_map(|(id, boost)| Step::Input(Input::Boost(id, boost))),
+            10 => Just(Step::Update),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn mass_is_conserved(seed: u64, steps in vec(step(), 1..400)) {
+            let config = GameConfig {
+                seed,
+                arena: Arena::Rect { width: 600., height: 600. },
+                ..Default::default()
+            };
+
+            let rng = config.rng();
+            let map = Map { food_mass: 200., ..Map::from_config(&config) };
+            let mut state = GameState::from_map(config, map, rng);
+
+            for step in steps {
+                let mass_before = state.world.mass() as f64;
+                let brought_before = state.world.ledger.total();
+
+                match step {
+                    Step::Input(input) => state.apply(input),
+                    Step::Update => state.update(state.config.delta_time()),
+                }
+
+                let ledger = state.world.ledger;
+
+                prop_assert!(ledger.balances(&state.world), "{:?}", ledger);
+
+                let changed = state.world.mass() as f64 - mass_before;
+                let brought = ledger.total() - brought_before;
+
+                prop_assert!((changed - brought).abs() <= 1e-3 * ledger.total());
+
+                prop_assert!(state.world.clots.iter().all(|clot| clot.amount > 0.));
+            }
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::f32::consts::PI;
+
+use ecolor::Color32;
+use emath::{Pos2, Vec2};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{Arena, MassClot, Obstacle};
+use crate::GameConfig;
+
+/// What a world is made of. Missing fields are taken from the default map
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Map {
+    pub arena: Arena,
+    /// Mass of the food the world starts with.
+    /// Food is spawned while the slithers and the clots together are lighter than that
+    pub food_mass: f32,
+    /// Where food appears, anywhere in the arena if there are no zones
+    pub food_zones: Vec<FoodZone>,
+    /// Where slithers appear, anywhere in the arena if there are no regions
+    pub spawn_regions: Vec<Region>,
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Map {
+    /// Checks the map can be played on
+    pub fn validate(&self) -> Result<(), String> {
+        self.arena.validate()?;
+
+        if !self.food_mass.is_finite() || self.food_mass < 0. {
+            return Err(format!(
+                "food_mass can't be negative, got {}",
+                self.food_mass
+            ));
+        }
+
+        for zone in &self.food_zones {
+            if !zone.density.is_finite() || zone.density < 0. {
+                return Err(format!(
+                    "density of a food zone can't be negative, got {}",
+                    zone.density
+                ));
+            }
+
+            zone.region.validate(&self.arena)?;
+        }
+
+        if !self.food_zones.is_empty() && self.food_zones.iter().all(|zone| zone.density == 0.) {
+            return Err("at least one food zone must have a positive density".to_string());
+        }
+
+        for region in &self.spawn_regions {
+            region.validate(&self.arena)?;
+        }
+
+        for obstacle in &self.obstacles {
+            obstacle.validate(&self.arena)?;
+        }
+
+        Ok(())
+    }
+
+    /// the plain map of the arena and the obstacles of the config
+    pub fn from_config(config: &GameConfig) -> Self {
+        Self {
+            arena: config.arena,
+            food_mass: config.target_mass_density * config.arena.area(),
+            food_zones: Vec::new(),
+            spawn_regions: Vec::new(),
+            obstacles: config.obstacles.clone(),
+        }
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::from_config(&GameConfig::default())
+    }
+}
+
+/// A part of the world with its own amount and colors of food
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FoodZone {
+    pub region: Region,
+    /// How dense the food is here compared to other zones.
+    /// A clot lands in a zone with a chance proportional to its density and area
+    #[serde(default = "FoodZone::default_density")]
+    pub density: f32,
+    /// Colors of the food here, random if it's empty
+    #[serde(default)]
+    pub palette: Vec<Color32>,
+}
+
+impl FoodZone {
+    fn default_density() -> f32 {
+        1.
+    }
+
+    pub fn random_color(&self, rng: &mut impl Rng) -> Color32 {
+        self.palette
+            .choose(rng)
+            .copied()
+            .unwrap_or_else(|| MassClot::random_color(rng))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    Rect { min: Pos2, max: Pos2 },
+    Circle { center: Pos2, radius: f32 },
+}
+
+impl Region {
+    pub fn area(&self) -> f32 {
+        match *self {
+            Region::Rect { min, max } => (max.x - min.x) * (max.y - min.y),
+            Region::Circle { radius, .. } => PI * radius * radius,
+        }
+    }
+
+    /// Checks the region isn't empty and lies inside the arena
+    pub fn validate(&self, arena: &Arena) -> Result<(), String> {
+        match *self {
+            Region::Rect { min, max } => {
+                if !min.is_finite() || !max.is_finite() {
+                    return Err(format!(
+                        "region from {min:?} to {max:?} must be made of numbers"
+                    ));
+                }
+
+                if min.x >= max.x || min.y >= max.y {
+                    return Err(format!(
+                        "region min {min:?} must be less than its max {max:?}"
+                    ));
+                }
+
+                // a torus has no border, any region fits in it
+                let corners = [min, Pos2::new(min.x, max.y), max, Pos2::new(max.x, min.y)];
+
+                if !arena.wraps() && !corners.iter().all(|&corner| arena.contains(corner, 0.)) {
+                    return Err(format!(
+                        "region from {min:?} to {max:?} doesn't fit in the arena"
+                    ));
+                }
+            }
+
+            Region::Circle { center, radius } => {
+                if !radius.is_finite() || radius <= 0. {
+                    return Err(format!("radius of a region must be positive, got {radius}"));
+                }
+
+                if !center.is_finite() {
+                    return Err(format!(
+                        "center of a region must be a number, got {center:?}"
+                    ));
+                }
+
+                if !arena.wraps() && arena.depth(center) < radius {
+                    return Err(format!(
+                        "region around {center:?} of radius {radius} doesn't fit in the arena"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn random_pos(&self, rng: &mut impl Rng) -> Pos2 {
+        match *self {
+            Region::Rect { min, max } => {
+                Pos2::new(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y))
+            }
+
+            Region::Circle { center, radius } => {
+                // sqrt makes points spread evenly over the circle area
+                let distance = radius * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..2. * PI);
+
+                center + Vec2::angled(angle) * distance
+            }
+        }
+    }
+
+    /// the nearest point inside
+    pub fn clamp(&self, pos: Pos2) -> Pos2 {
+        match *self {
+            Region::Rect { min, max } => pos.clamp(min, max),
+
+            Region::Circle { center, radius } => {
+                let from_center = pos - center;
+
+                if from_center.length() <= radius {
+                    pos
+                } else {
+                    center + from_center.normalized() * radius
+                }
+            }
+        }
+    }
+}
+
+/// Index of a random one of the weighted things, `None` if there is nothing with a positive weight
+pub(super) fn choose_weighted(
+    rng: &mut impl Rng,
+    weights: impl IntoIterator<Item = f32>,
+) -> Option<usize> {
+    WeightedIndex::new(weights)
+        .ok()
+        .map(|index| index.sample(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use emath::Pos2;
+
+    use super::{FoodZone, Map, Region};
+    use crate::world::{FoodSpawner, World};
+    use crate::{Arena, FoodDistribution, GameConfig, GameState, Input, SlitherID};
+
+    fn zone(region: Region) -> FoodZone {
+        FoodZone {
+            region,
+            density: 1.,
+            palette: vec![Color32::RED],
+        }
+    }
+
+    #[test]
+    fn food_and_slithers_stay_in_their_regions() {
+        let config = GameConfig::default();
+
+        let food = Region::Circle {
+            center: Pos2::new(500., 500.),
+            radius: 200.,
+        };
+        let spawn = Region::Rect {
+            min: Pos2::new(1200., 1200.),
+            max: Pos2::new(1800., 1800.),
+        };
+
+        let map = Map {
+            food_mass: 3000.,
+            food_zones: vec![zone(food)],
+            spawn_regions: vec![spawn],
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::from_map(config, map, rng);
+
+        for id in 0..10 {
+            state.apply(Input::Join {
+                id: SlitherID(id),
+                color: Color32::WHITE,
+                nickname: String::new(),
+            });
+        }
+
+        for _ in 0..10 {
+            state.update(state.config.delta_time());
+        }
+
+        assert!(state.world.clots.mass() > 2500.);
+
+        for clot in state.world.clots.iter() {
+            assert!(clot.pos.distance(Pos2::new(500., 500.)) < 200.1);
+            assert_eq!(clot.color, Color32::RED);
+        }
+
+        for (_, slither) in state.world.slithers.iter() {
+            let head = slither.body.head();
+
+            assert!((1100.0..1900.).contains(&head.x) && (1100.0..1900.).contains(&head.y));
+        }
+    }
+
+    #[test]
+    fn clusters_stay_in_their_zone() {
+        let config = GameConfig {
+            food_distribution: FoodDistribution::Clustered {
+                radius: 150.,
+                size: 8,
+            },
+            ..Default::default()
+        };
+
+        // the clusters are wider than the zone, their clots are pulled back into it
+        let food = Region::Rect {
+            min: Pos2::new(400., 400.),
+            max: Pos2::new(500., 500.),
+        };
+
+        let mut spawner = FoodSpawner::new(2000., vec![zone(food)]);
+        let mut world = World::default();
+        let mut rng = config.rng();
+
+        for _ in 0..10 {
+            spawner.update(&mut world, &config, &mut rng, 1.);
+        }
+
+        assert!(world.clots.len() > 16);
+
+        for clot in world.clots.iter() {
+            assert!((400.0..=500.).contains(&clot.pos.x) && (400.0..=500.).contains(&clot.pos.y));
+            assert_eq!(clot.color, Color32::RED);
+        }
+    }
+
+    #[test]
+    fn regions_out_of_the_arena_are_rejected() {
+        let arena = Arena::Circle { radius: 500. };
+
+        let inverted = Region::Rect {
+            min: Pos2::new(600., 600.),
+            max: Pos2::new(400., 400.),
+        };
+        let corner = Region::Rect {
+            min: Pos2::new(0., 0.),
+            max: Pos2::new(100., 100.),
+        };
+        let inside = Region::Circle {
+            center: Pos2::new(500., 500.),
+            radius: 200.,
+        };
+
+        for (region, valid) in [(inverted, false), (corner, false), (inside, true)] {
+            let map = Map {
+                arena,
+                spawn_regions: vec![region],
+                ..Default::default()
+            };
+
+            assert_eq!(map.validate().is_ok(), valid, "{region:?}");
+        }
+
+        let map = Map {
+            arena,
+            food_zones: vec![FoodZone {
+                density: -1.,
+                ..zone(inside)
+            }],
+            ..Default::default()
+        };
+
+        assert!(map.validate().is_err());
+    }
+
+    #[test]
+    fn maps_without_a_size_are_rejected() {
+        let sizes = [
+            Arena::Rect {
+                width: 0.,
+                height: 500.,
+            },
+            Arena::Torus {
+                width: 500.,
+                height: f32::NAN,
+            },
+            Arena::Circle { radius: -100. },
+            Arena::Circle {
+                radius: f32::INFINITY,
+            },
+        ];
+
+        for arena in sizes {
+            let map = Map {
+                arena,
+                ..Default::default()
+            };
+
+            assert!(map.validate().is_err(), "{arena:?}");
+        }
+
+        let nan_region = Region::Rect {
+            min: Pos2::new(f32::NAN, 100.),
+            max: Pos2::new(200., 200.),
+        };
+        let map = Map {
+            arena: Arena::Torus {
+                width: 500.,
+                height: 500.,
+            },
+            spawn_regions: vec![nan_region],
+            ..Default::default()
+        };
+
+        assert!(map.validate().is_err());
+    }
+}
@@ -1,55 +1,216 @@
 use ecolor::Color32;
-use emath::Pos2;
-use rand::rngs::ThreadRng;
-use rand::{thread_rng, Rng};
-use serde::{Deserialize, Serialize};
+use emath::{Pos2, Vec2};
+use rand::Rng;
+use serde::ser::{SerializeSeq, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use super::{MAX_CLOT_MASS, MIN_CLOT_MASS};
+use super::Arena;
+use crate::grid::Grid;
+
+/// Side of a square the clots are bucketed by
+const GRID_CELL_SIZE: f32 = 64.;
 
-#[derive(Default, Serialize, Deserialize)]
 pub struct MassClots {
-    data: Vec<MassClot>,
+    grid: Grid<MassClot>,
+    /// the biggest radius of all the clots ever added
+    max_radius: f32,
+    /// sum of all the clots amounts, kept in double precision so it doesn't drift
+    mass: f64,
+    /// Where the drifting clots are, so only their buckets are visited to move and stop them.
+    /// It may also point at eaten clots or hold a place more than once
+    drifting: Vec<Pos2>,
 }
 
 impl MassClots {
-    pub fn new(width: f32, height: f32, mut total_mass: f32) -> Self {
-        let mut rng = thread_rng();
-
-        let random_color = |rng: &mut ThreadRng| {
-            Color32::from_rgb(
-                rng.gen_range(0..127) + 128,
-                rng.gen_range(0..127) + 128,
-                rng.gen_range(0..127) + 128,
-            )
-        };
+    pub fn add(&mut self, clot: MassClot) {
+        self.max_radius = self.max_radius.max(clot.radius());
+        self.mass += clot.amount as f64;
+
+        if clot.drift.is_some() {
+            self.drifting.push(clot.pos);
+        }
+
+        self.grid.insert(clot.pos, clot);
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(MassClot) -> bool) {
+        let mut removed_mass = 0.;
+
+        self.grid.retain(|&clot| {
+            let keep = f(clot);
+
+            if !keep {
+                removed_mass += clot.amount as f64;
+            }
 
-        let mut data =
-            Vec::with_capacity((total_mass * 2. / (MAX_CLOT_MASS - MIN_CLOT_MASS)) as usize);
+            keep
+        });
 
-        while total_mass > MIN_CLOT_MASS {
-            let mass = rng.gen_range(MIN_CLOT_MASS..MAX_CLOT_MASS);
+        self.mass -= removed_mass;
+    }
+
+    /// Like [`MassClots::retain`], but visits only clots which can intersect the circle.
+    /// Some of the visited clots may be farther, so `f` has to check the distance on its own
+    pub fn retain_near(&mut self, center: Pos2, radius: f32, mut f: impl FnMut(MassClot) -> bool) {
+        let mut removed_mass = 0.;
+
+        self.grid
+            .retain_near(center, radius + self.max_radius, |&clot| {
+                let keep = f(clot);
 
-            total_mass -= mass;
+                if !keep {
+                    removed_mass += clot.amount as f64;
+                }
+
+                keep
+            });
+
+        self.mass -= removed_mass;
+    }
 
-            let color = random_color(&mut rng);
-            let clot = MassClot::random_in(&mut rng, width, height, mass, color);
+    /// Adds the velocity `f` gives to the drift of clots which can intersect the circle.
+    /// Some of the visited clots may be farther, so `f` has to check the distance on its own.
+    /// The clots stay in place until [`MassClots::drift`]
+    pub fn pull_near(&mut self, center: Pos2, radius: f32, mut f: impl FnMut(MassClot) -> Vec2) {
+        let drifting = &mut self.drifting;
+
+        self.grid
+            .update_near(center, radius + self.max_radius, |clot| {
+                let pull = f(*clot);
+
+                if pull == Vec2::ZERO {
+                    return;
+                }
+
+                if clot.drift.is_none() {
+                    drifting.push(clot.pos);
+                }
+
+                clot.drift = Some(clot.drift.unwrap_or_default() + pull);
+            });
+    }
 
-            data.push(clot);
+    /// moves every pulled clot once by all the pulls it got, they keep their mass
+    pub fn drift(&mut self, delta_time: f32, arena: &Arena) {
+        let mut moved = Vec::new();
+
+        // a radius of zero visits only the bucket of the place
+        for pos in std::mem::take(&mut self.drifting) {
+            self.grid.retain_near(pos, 0., |&clot| {
+                let Some(drift) = clot.drift else {
+                    return true;
+                };
+
+                moved.push(MassClot {
+                    pos: arena.wrap(clot.pos + drift * delta_time),
+                    ..clot
+                });
+
+                false
+            });
         }
 
-        Self { data }
+        for clot in moved {
+            self.drifting.push(clot.pos);
+            self.grid.insert(clot.pos, clot);
+        }
     }
 
-    pub fn add(&mut self, clot: MassClot) {
-        self.data.push(clot);
+    /// stops the clots drifting since the last tick, the ones which still should drift are pulled again
+    pub fn settle(&mut self) {
+        for pos in std::mem::take(&mut self.drifting) {
+            self.grid.update_near(pos, 0., |clot| clot.drift = None);
+        }
     }
 
-    pub fn retain(&mut self, mut f: impl FnMut(MassClot) -> bool) {
-        self.data.retain(|&clot| f(clot));
+    /// clots which can intersect the circle, some of them may be farther
+    pub fn near(&self, center: Pos2, radius: f32) -> impl Iterator<Item = MassClot> + '_ {
+        self.grid.near(center, radius + self.max_radius).copied()
+    }
+
+    /// the biggest radius a clot ever had here
+    pub fn max_radius(&self) -> f32 {
+        self.max_radius
     }
 
     pub fn iter(&self) -> impl Iterator<Item = MassClot> + '_ {
-        self.data.iter().copied()
+        self.grid.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.grid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grid.is_empty()
+    }
+
+    /// total mass of all the clots
+    pub fn mass(&self) -> f32 {
+        self.mass as f32
+    }
+}
+
+impl Default for MassClots {
+    fn default() -> Self {
+        Self {
+            grid: Grid::new(GRID_CELL_SIZE),
+            max_radius: 0.,
+            mass: 0.,
+            drifting: Vec::new(),
+        }
+    }
+}
+
+impl FromIterator<MassClot> for MassClots {
+    fn from_iter<I: IntoIterator<Item = MassClot>>(iter: I) -> Self {
+        let mut clots = Self::default();
+
+        for clot in iter {
+            clots.add(clot);
+        }
+
+        clots
+    }
+}
+
+/// The grid is an implementation detail, so clots are sent as a plain sequence.
+/// The bookkeeping goes along, so a copy works exactly the same as the original
+impl Serialize for MassClots {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(3)?;
+
+        tuple.serialize_element(&Sequence(self))?;
+        tuple.serialize_element(&self.max_radius)?;
+        tuple.serialize_element(&self.mass)?;
+
+        tuple.end()
+    }
+}
+
+struct Sequence<'a>(&'a MassClots);
+
+impl Serialize for Sequence<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+
+        for clot in self.0.iter() {
+            seq.serialize_element(&clot)?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for MassClots {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (clots, max_radius, mass) = <(Vec<MassClot>, f32, f64)>::deserialize(deserializer)?;
+
+        let mut clots = Self::from_iter(clots);
+        clots.max_radius = max_radius;
+        clots.mass = mass;
+
+        Ok(clots)
     }
 }
 
@@ -58,22 +219,96 @@ pub struct MassClot {
     pub pos: Pos2,
     pub amount: f32,
     pub color: Color32,
+    /// Velocity of the clot during the last tick, it drifts while heads suck it in.
+    /// Most clots lie still, so they aren't sent with a zero velocity
+    pub drift: Option<Vec2>,
 }
 
 impl MassClot {
-    pub fn random_in(
-        rng: &mut impl Rng,
-        width: f32,
-        height: f32,
-        amount: f32,
-        color: Color32,
-    ) -> Self {
-        let pos = Pos2::new(rng.gen_range(0.0..width), rng.gen_range(0.0..height));
+    /// a clot lying still
+    pub fn new(pos: Pos2, amount: f32, color: Color32) -> Self {
+        Self {
+            pos,
+            amount,
+            color,
+            drift: None,
+        }
+    }
 
-        Self { pos, amount, color }
+    pub fn random_color(rng: &mut impl Rng) -> Color32 {
+        Color32::from_rgb(
+            rng.gen_range(0..127) + 128,
+            rng.gen_range(0..127) + 128,
+            rng.gen_range(0..127) + 128,
+        )
     }
 
     pub fn radius(&self) -> f32 {
         self.amount.sqrt()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+    use emath::{Pos2, Vec2};
+
+    use super::{MassClot, MassClots};
+    use crate::Arena;
+
+    #[test]
+    fn pulled_clot_moves_once_by_all_the_pulls() {
+        let arena = Arena::default();
+        let mut clots =
+            MassClots::from_iter([MassClot::new(Pos2::new(100., 100.), 10., Color32::WHITE)]);
+
+        clots.pull_near(Pos2::new(100., 100.), 50., |_| Vec2::new(10., 0.));
+        clots.pull_near(Pos2::new(100., 100.), 50., |_| Vec2::new(0., 20.));
+        clots.drift(0.5, &arena);
+
+        let clot = clots.iter().next().unwrap();
+
+        assert_eq!(clot.pos, Pos2::new(105., 110.));
+        assert_eq!(clot.drift, Some(Vec2::new(10., 20.)));
+    }
+
+    #[test]
+    fn settled_clot_stays_in_place() {
+        let arena = Arena::default();
+        let still = MassClot::new(Pos2::new(300., 300.), 10., Color32::WHITE);
+        let mut clots = MassClots::from_iter([
+            MassClot::new(Pos2::new(100., 100.), 10., Color32::WHITE),
+            still,
+        ]);
+
+        clots.pull_near(Pos2::new(100., 100.), 50., |_| Vec2::new(100., 0.));
+        clots.drift(1., &arena);
+
+        // the clot crossed into another bucket, it's still found there
+        clots.settle();
+        clots.drift(1., &arena);
+
+        let mut moved = clots
+            .iter()
+            .map(|clot| (clot.pos, clot.drift))
+            .collect::<Vec<_>>();
+        moved.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+
+        assert_eq!(moved, [(Pos2::new(200., 100.), None), (still.pos, None)]);
+    }
+
+    #[test]
+    fn still_clot_is_sent_without_drift() {
+        let still = MassClot::new(Pos2::new(100., 100.), 10., Color32::WHITE);
+        let drifting = MassClot {
+            drift: Some(Vec2::X),
+            ..still
+        };
+
+        let size = |clot: &MassClot| bincode::serialize(clot).unwrap().len();
+
+        // position, amount, color and the tag of the missing drift
+        assert_eq!(size(&still), 8 + 4 + 4 + 1);
+        assert_eq!(size(&drifting), size(&still) + 8);
+    }
+}
@@ -0,0 +1,338 @@
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::Arena;
+use crate::Segment;
+
+/// Something standing still which kills a slither touching it
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Obstacle {
+    Circle {
+        center: Pos2,
+        radius: f32,
+    },
+    /// a line through the points, `width` thick
+    Wall {
+        points: Vec<Pos2>,
+        width: f32,
+    },
+    /// a solid convex polygon with the points as its corners
+    Polygon {
+        points: Vec<Pos2>,
+    },
+}
+
+impl Obstacle {
+    /// Checks the obstacle has a shape and fits in the arena, the other methods rely on it
+    pub fn validate(&self, arena: &Arena) -> Result<(), String> {
+        match self {
+            Obstacle::Circle { center, radius } if !center.is_finite() || !radius.is_finite() => {
+                Err(format!(
+                    "obstacle at {center:?} of radius {radius} must be made of numbers"
+                ))
+            }
+
+            Obstacle::Circle { radius, .. } if *radius < 0. => Err(format!(
+                "radius of an obstacle can't be negative, got {radius}"
+            )),
+
+            Obstacle::Wall { points, .. } | Obstacle::Polygon { points } if points.is_empty() => {
+                Err("walls and polygons must have at least one point".to_string())
+            }
+
+            Obstacle::Wall { points, .. } | Obstacle::Polygon { points }
+                if points.iter().any(|point| !point.is_finite()) =>
+            {
+                Err(format!(
+                    "points of an obstacle must be numbers, got {points:?}"
+                ))
+            }
+
+            Obstacle::Wall { width, .. } if !width.is_finite() || *width < 0. => {
+                Err(format!("width of a wall can't be negative, got {width}"))
+            }
+
+            _ => Ok(()),
+        }?;
+
+        // points are measured against the copy of the obstacle nearest to its anchor,
+        // which is the one they touch only if the obstacle spans less than half the torus
+        if let Arena::Torus { width, height } = *arena {
+            let size = self.size();
+
+            if size.x > width / 2. || size.y > height / 2. {
+                return Err(format!(
+                    "obstacle of size {size:?} doesn't fit in half of the arena of {width}x{height}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Distance from the point to the obstacle, negative inside.
+    /// In a wrapping arena it's measured to the nearest copy of the point
+    pub fn distance(&self, pos: Pos2, arena: &Arena) -> f32 {
+        let pos = arena.copy_near(pos, self.anchor());
+
+        match self {
+            Obstacle::Circle { center, radius } => pos.distance(*center) - radius,
+
+            Obstacle::Wall { width, .. } => self.edge_distance(pos) - width / 2.,
+
+            Obstacle::Polygon { .. } if self.contains(pos) => -self.edge_distance(pos),
+
+            Obstacle::Polygon { .. } => self.edge_distance(pos),
+        }
+    }
+
+    /// the way from the point to the nearest point of the obstacle's edge
+    pub fn offset(&self, pos: Pos2, arena: &Arena) -> Vec2 {
+        let pos = arena.copy_near(pos, self.anchor());
+
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let from_center = pos - *center;
+
+                if from_center == Vec2::ZERO {
+                    return Vec2::X * *radius;
+                }
+
+                from_center.normalized() * (radius - from_center.length())
+            }
+
+            _ => self.closest(pos) - pos,
+        }
+    }
+
+    /// whether a circle of `radius` touches the obstacle anywhere on its way
+    pub fn touches(&self, path: Segment, radius: f32, arena: &Arena) -> bool {
+        let path = arena.align(path, self.anchor());
+
+        match self {
+            Obstacle::Circle {
+                center,
+                radius: own,
+            } => path.distance_sq_to(*center) < (radius + own).powi(2),
+
+            Obstacle::Wall { width, .. } => {
+                let safe_dist = radius + width / 2.;
+
+                self.edges()
+                    .any(|edge| path.distance_sq(&edge) < safe_dist.powi(2))
+            }
+
+            Obstacle::Polygon { .. } => {
+                self.contains(path.end)
+                    || self
+                        .edges()
+                        .any(|edge| path.distance_sq(&edge) < radius.powi(2))
+            }
+        }
+    }
+
+    /// a point of the obstacle, which its copies in a wrapping arena are found by
+    fn anchor(&self) -> Pos2 {
+        match self {
+            Obstacle::Circle { center, .. } => *center,
+            Obstacle::Wall { points, .. } | Obstacle::Polygon { points } => points[0],
+        }
+    }
+
+    /// sides of the bounding box of the obstacle
+    fn size(&self) -> Vec2 {
+        let (points, thickness) = match self {
+            Obstacle::Circle { radius, .. } => return Vec2::splat(radius * 2.),
+            Obstacle::Wall { points, width } => (points, *width),
+            Obstacle::Polygon { points } => (points, 0.),
+        };
+
+        let min = points.iter().fold(points[0], |min, point| min.min(*point));
+        let max = points.iter().fold(points[0], |max, point| max.max(*point));
+
+        max - min + Vec2::splat(thickness)
+    }
+
+    /// straight parts of a wall or sides of a polygon
+    fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let points: &[Pos2] = match self {
+            Obstacle::Circle { .. } => &[],
+            Obstacle::Wall { points, .. } | Obstacle::Polygon { points } => points,
+        };
+
+        let closing = match self {
+            Obstacle::Polygon { points } if points.len() > 2 => {
+                Some(Segment::new(*points.last().unwrap(), points[0]))
+            }
+
+            _ => None,
+        };
+
+        points
+            .windows(2)
+            .map(|pair| Segment::new(pair[0], pair[1]))
+            .chain(closing)
+            // a single point is a wall too
+            .chain((points.len() == 1).then(|| Segment::new(points[0], points[0])))
+    }
+
+    fn closest(&self, pos: Pos2) -> Pos2 {
+        self.edges()
+            .map(|edge| edge.closest(pos))
+            .min_by(|a, b| a.distance_sq(pos).total_cmp(&b.distance_sq(pos)))
+            .unwrap_or(pos)
+    }
+
+    fn edge_distance(&self, pos: Pos2) -> f32 {
+        self.closest(pos).distance(pos)
+    }
+
+    /// whether the point is inside a polygon, counting crossings of a ray going right
+    fn contains(&self, pos: Pos2) -> bool {
+        let Obstacle::Polygon { points } = self else {
+            return false;
+        };
+
+        let mut inside = false;
+
+        for edge in self.edges().take(points.len()) {
+            let (a, b) = (edge.start, edge.end);
+
+            if (a.y > pos.y) != (b.y > pos.y) {
+                let x = a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x);
+
+                if pos.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::{Pos2, Vec2};
+
+    use super::Obstacle;
+    use crate::world::Arena;
+    use crate::Segment;
+
+    fn square() -> Obstacle {
+        Obstacle::Polygon {
+            points: vec![
+                Pos2::new(100., 100.),
+                Pos2::new(200., 100.),
+                Pos2::new(200., 200.),
+                Pos2::new(100., 200.),
+            ],
+        }
+    }
+
+    #[test]
+    fn polygon_distance_is_negative_inside() {
+        let arena = Arena::default();
+
+        assert_eq!(square().distance(Pos2::new(150., 120.), &arena), -20.);
+        assert_eq!(square().distance(Pos2::new(150., 50.), &arena), 50.);
+    }
+
+    #[test]
+    fn shapes_without_points_are_rejected() {
+        let wall = Obstacle::Wall {
+            points: Vec::new(),
+            width: 10.,
+        };
+        let polygon = Obstacle::Polygon { points: Vec::new() };
+
+        let arena = Arena::default();
+
+        assert!(wall.validate(&arena).is_err());
+        assert!(polygon.validate(&arena).is_err());
+        assert!(square().validate(&arena).is_ok());
+    }
+
+    #[test]
+    fn shapes_which_arent_numbers_are_rejected() {
+        let circle = Obstacle::Circle {
+            center: Pos2::new(100., 100.),
+            radius: f32::NAN,
+        };
+        let wall = Obstacle::Wall {
+            points: vec![Pos2::new(100., 100.), Pos2::new(f32::INFINITY, 100.)],
+            width: 10.,
+        };
+        let thick_wall = Obstacle::Wall {
+            points: vec![Pos2::new(100., 100.)],
+            width: f32::NAN,
+        };
+
+        let arena = Arena::default();
+
+        assert!(circle.validate(&arena).is_err());
+        assert!(wall.validate(&arena).is_err());
+        assert!(thick_wall.validate(&arena).is_err());
+    }
+
+    #[test]
+    fn obstacles_over_half_the_torus_are_rejected() {
+        let arena = Arena::Torus {
+            width: 1000.,
+            height: 400.,
+        };
+        let long_wall = Obstacle::Wall {
+            points: vec![Pos2::new(100., 100.), Pos2::new(700., 100.)],
+            width: 10.,
+        };
+        let tall_circle = Obstacle::Circle {
+            center: Pos2::new(500., 200.),
+            radius: 150.,
+        };
+
+        assert!(long_wall.validate(&arena).is_err());
+        assert!(tall_circle.validate(&arena).is_err());
+        assert!(square().validate(&arena).is_ok());
+
+        // a bounded arena has no copies to confuse
+        let rect = Arena::Rect {
+            width: 1000.,
+            height: 400.,
+        };
+
+        assert!(long_wall.validate(&rect).is_ok());
+        assert!(tall_circle.validate(&rect).is_ok());
+    }
+
+    #[test]
+    fn fast_head_doesnt_pass_through_a_wall() {
+        let arena = Arena::default();
+        let wall = Obstacle::Wall {
+            points: vec![Pos2::new(500., 0.), Pos2::new(500., 1000.)],
+            width: 10.,
+        };
+
+        let path = Segment::new(Pos2::new(400., 300.), Pos2::new(600., 300.));
+
+        assert!(wall.touches(path, 5., &arena));
+        assert!(!wall.touches(Segment::new(path.start, path.start), 5., &arena));
+    }
+
+    #[test]
+    fn obstacles_are_found_across_torus_edges() {
+        let arena = Arena::Torus {
+            width: 1000.,
+            height: 1000.,
+        };
+        let circle = Obstacle::Circle {
+            center: Pos2::new(10., 500.),
+            radius: 30.,
+        };
+
+        let pos = Pos2::new(990., 500.);
+
+        assert_eq!(circle.distance(pos, &arena), -10.);
+        assert_eq!(circle.offset(pos, &arena), Vec2::new(-10., 0.));
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::ops::{Index, IndexMut};
 
 use serde::{Deserialize, Serialize};
@@ -12,7 +12,8 @@ pub struct SlitherID;
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct Slithers {
-    data: HashMap<SlitherID, Slither>,
+    /// sorted, so the slithers are always visited in the same order
+    data: BTreeMap<SlitherID, Slither>,
 }
 
 impl Slithers {
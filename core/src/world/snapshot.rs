@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use emath::Pos2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{MassLedger, World};
+
+/// The world with the bookkeeping of the server and the trails of the heads,
+/// which clients don't get every tick. Replay keyframes need them, so a restored game goes on the same
+pub fn serialize<S: Serializer>(world: &World, serializer: S) -> Result<S::Ok, S::Error> {
+    let trails: Vec<_> = world
+        .slithers
+        .iter()
+        .map(|(_, slither)| slither.body.trail())
+        .collect();
+
+    (world, &world.ledger, trails).serialize(serializer)
+}
+
+/// reads a world written by [`serialize`]
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<World, D::Error> {
+    let (mut world, ledger, trails) =
+        <(World, MassLedger, Vec<VecDeque<Pos2>>)>::deserialize(deserializer)?;
+
+    world.ledger = ledger;
+
+    // the slithers are visited in the same order as they were written
+    for ((_, slither), trail) in world.slithers.iter_mut().zip(trails) {
+        slither.body.restore_trail(trail);
+    }
+
+    Ok(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+
+    use crate::{GameConfig, GameState, Input, Map, SlitherID, World};
+
+    #[test]
+    fn only_keyframes_keep_the_ledger() {
+        let config = GameConfig::default();
+        let rng = config.rng();
+        let state = GameState::from_map(config.clone(), Map::from_config(&config), rng);
+
+        let spawned = state.world.ledger.spawned;
+
+        assert!(spawned > 0.);
+
+        let sent: World = bincode::deserialize(&bincode::serialize(&state.world).unwrap()).unwrap();
+
+        assert_eq!(sent.ledger.spawned, 0.);
+
+        let restored: GameState =
+            bincode::deserialize(&bincode::serialize(&state).unwrap()).unwrap();
+
+        assert_eq!(restored.world.ledger.spawned, spawned);
+    }
+
+    #[test]
+    fn only_keyframes_keep_the_trails() {
+        let config = GameConfig::default();
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+
+        state.apply(Input::Join {
+            id: SlitherID(0),
+            color: Color32::WHITE,
+            nickname: String::new(),
+        });
+
+        for _ in 0..30 {
+            state.update(state.config.delta_time());
+        }
+
+        let trail = |world: &World| world.slithers[SlitherID(0)].body.trail().clone();
+
+        assert!(trail(&state.world).len() > 1);
+
+        let sent: World = bincode::deserialize(&bincode::serialize(&state.world).unwrap()).unwrap();
+
+        assert!(trail(&sent).is_empty());
+        assert_eq!(
+            sent.slithers[SlitherID(0)].body.cells(),
+            state.world.slithers[SlitherID(0)].body.cells()
+        );
+
+        let restored: GameState =
+            bincode::deserialize(&bincode::serialize(&state).unwrap()).unwrap();
+
+        assert_eq!(trail(&restored.world), trail(&state.world));
+    }
+}
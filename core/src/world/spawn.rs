@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+
+use emath::{Pos2, Vec2};
+use rand::Rng;
+
+use super::{map, BodyIndex, Region, World};
+use crate::GameConfig;
+
+struct Candidate {
+    pos: Pos2,
+    /// distance to the nearest wall, obstacle or body
+    clearance: f32,
+}
+
+impl World {
+    /// Chooses where a new slither appears in the regions and where it heads.
+    ///
+    /// Random places are tried until one is far enough from bodies, obstacles and walls.
+    /// If there is no such place, the most spacious one of the tried is taken
+    pub fn spawn_point(
+        &self,
+        rng: &mut impl Rng,
+        regions: &[Region],
+        config: &GameConfig,
+    ) -> (Pos2, f32) {
+        let bodies = BodyIndex::new(&self.slithers, config);
+        let safe_dist = config.spawn_safe_distance;
+
+        let mut best: Option<Candidate> = None;
+
+        for _ in 0..config.spawn_candidates.max(1) {
+            let pos = random_spawn_pos(rng, regions, safe_dist, config);
+            let clearance = self.clearance(&bodies, pos, safe_dist, config);
+
+            if best.as_ref().is_none_or(|best| clearance > best.clearance) {
+                best = Some(Candidate { pos, clearance });
+            }
+
+            if clearance >= safe_dist {
+                break;
+            }
+        }
+
+        let pos = best.unwrap().pos;
+
+        let dir = match self.danger(&bodies, pos, 2. * safe_dist, config) {
+            Some(danger) => (-danger).angle(),
+            None => rng.gen_range(0.0..2. * PI),
+        };
+
+        (pos, dir)
+    }
+
+    /// distance to the nearest wall, obstacle or body, but not more than `max_dist`
+    fn clearance(&self, bodies: &BodyIndex, pos: Pos2, max_dist: f32, config: &GameConfig) -> f32 {
+        let arena = &config.arena;
+
+        let to_wall = arena.depth(pos);
+
+        let to_obstacles = config
+            .obstacles
+            .iter()
+            .map(|obstacle| obstacle.distance(pos, arena));
+
+        let to_bodies = bodies
+            .near(pos, max_dist)
+            .map(|cell| arena.distance(pos, cell.pos) - cell.radius);
+
+        to_obstacles
+            .chain(to_bodies)
+            .fold(to_wall.min(max_dist), f32::min)
+    }
+
+    /// Direction to the things around which can kill, closer things weigh more.
+    /// Every slither counts once by its nearest cell, so long bodies don't outweigh walls
+    fn danger(
+        &self,
+        bodies: &BodyIndex,
+        pos: Pos2,
+        look_dist: f32,
+        config: &GameConfig,
+    ) -> Option<Vec2> {
+        let arena = &config.arena;
+
+        let weight = |to: Vec2| {
+            let dist = to.length().max(1.);
+
+            if dist < look_dist {
+                to / dist / dist
+            } else {
+                Vec2::ZERO
+            }
+        };
+
+        let walls = arena.walls(pos);
+
+        let obstacles = config
+            .obstacles
+            .iter()
+            .map(|obstacle| obstacle.offset(pos, arena));
+
+        let mut nearest_cells = BTreeMap::new();
+
+        for cell in bodies.near(pos, look_dist) {
+            let to = arena.delta(pos, cell.pos);
+
+            nearest_cells
+                .entry(cell.id)
+                .and_modify(|nearest: &mut Vec2| {
+                    if to.length_sq() < nearest.length_sq() {
+                        *nearest = to;
+                    }
+                })
+                .or_insert(to);
+        }
+
+        let danger = walls
+            .into_iter()
+            .chain(obstacles)
+            .chain(nearest_cells.into_values())
+            .map(weight)
+            .fold(Vec2::ZERO, |sum, to| sum + to);
+
+        (danger != Vec2::ZERO).then_some(danger)
+    }
+}
+
+/// a random point of the regions, the bigger ones are chosen more often.
+/// Anywhere in the arena if there are no regions
+fn random_spawn_pos(
+    rng: &mut impl Rng,
+    regions: &[Region],
+    margin: f32,
+    config: &GameConfig,
+) -> Pos2 {
+    let weights = regions.iter().map(Region::area);
+
+    match map::choose_weighted(rng, weights) {
+        Some(index) => {
+            let region = regions[index];
+
+            config.free_pos(rng, margin, |rng| region.random_pos(rng))
+        }
+
+        None => config.random_pos(rng, margin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use ecolor::Color32;
+    use emath::Pos2;
+
+    use super::Region;
+    use crate::world::{Arena, Obstacle, SlitherID, World};
+    use crate::{GameConfig, Slither};
+
+    fn slither(pos: Pos2) -> Slither {
+        Slither::from_dir(Color32::WHITE, pos, 0., 100., String::new())
+    }
+
+    /// a region so small the slither appears right there
+    fn point(x: f32, y: f32) -> Region {
+        Region::Circle {
+            center: Pos2::new(x, y),
+            radius: 0.01,
+        }
+    }
+
+    #[test]
+    fn spawns_clear_of_bodies_walls_and_obstacles() {
+        let config = GameConfig {
+            arena: Arena::Rect {
+                width: 1500.,
+                height: 1500.,
+            },
+            obstacles: vec![Obstacle::Circle {
+                center: Pos2::new(300., 300.),
+                radius: 100.,
+            }],
+            ..Default::default()
+        };
+        let mut rng = config.rng();
+
+        let mut world = World::default();
+
+        for (id, pos) in [(400., 700.), (700., 400.), (700., 700.)]
+            .into_iter()
+            .enumerate()
+        {
+            world.add_slither(SlitherID(id as u32), slither(Pos2::from(pos)));
+        }
+
+        let safe_dist = config.spawn_safe_distance;
+
+        for _ in 0..50 {
+            let (pos, _) = world.spawn_point(&mut rng, &[], &config);
+
+            assert!(config.arena.depth(pos) >= safe_dist, "{pos:?}");
+            assert!(config.obstacles[0].distance(pos, &config.arena) >= safe_dist);
+
+            for (_, slither) in world.slithers.iter() {
+                let radius = slither.body.cell_radius(&config);
+
+                assert!(pos.distance(slither.body.head()) - radius >= safe_dist);
+            }
+        }
+    }
+
+    #[test]
+    fn heads_away_from_the_nearest_danger() {
+        let config = GameConfig::default();
+        let mut rng = config.rng();
+
+        let mut world = World::default();
+
+        // the left wall is the only thing around
+        let (_, dir) = world.spawn_point(&mut rng, &[point(50., 1000.)], &config);
+
+        assert!(dir.cos() > 0.9, "{dir}");
+
+        // a body on the right is closer than the walls
+        world.add_slither(SlitherID(0), slither(Pos2::new(1050., 1000.)));
+
+        let (_, dir) = world.spawn_point(&mut rng, &[point(1000., 1000.)], &config);
+
+        assert!((dir - PI).cos() > 0.9, "{dir}");
+    }
+
+    #[test]
+    fn crowded_arena_takes_the_most_spacious_place() {
+        // no point is as far from the walls as the safe distance
+        let config = GameConfig {
+            arena: Arena::Rect {
+                width: 200.,
+                height: 200.,
+            },
+            ..Default::default()
+        };
+        let mut rng = config.rng();
+
+        let world = World::default();
+
+        for _ in 0..20 {
+            let (pos, _) = world.spawn_point(&mut rng, &[], &config);
+
+            // the best of the tried places, a random one is that deep only once in four
+            assert!(config.arena.depth(pos) > 50.);
+        }
+    }
+}
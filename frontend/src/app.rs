@@ -1,22 +1,28 @@
 use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use egui::emath::TSTransform;
-use egui::{Align, CentralPanel, Color32, Margin, Pos2, Rect, Sense, Stroke, TextEdit};
+use egui::{Align, Align2, CentralPanel, Color32, Key, Margin, Pos2, Sense, TextEdit};
 
-use core::SlitherID;
+use core::{DeathCause, GameConfig, SlitherID};
 
 use crate::mutex_ext::MutexExt;
-use crate::painter::Painter;
+use crate::painter::{self, Painter};
+use crate::replay::{Replay, ReplayError};
 use crate::state::{State, StateUpdater};
 use crate::transfer::{SyncReceive, SyncSend};
 
+/// How long a kill stays in the feed
+const KILL_FEED_TIME: Duration = Duration::from_secs(8);
+
 pub enum App {
     Launcher(Launcher),
     Game(Game),
+    Replay(Box<Replay>),
     None,
 }
 
@@ -42,6 +48,16 @@ impl eframe::App for App {
                             *self = Self::Launcher(launcher);
                         }
                     }
+                } else if launcher.watch_clicked {
+                    match Replay::load(Path::new(&launcher.replay_path)) {
+                        Ok(replay) => {
+                            *self = Self::Replay(Box::new(replay));
+                        }
+
+                        Err(err) => {
+                            launcher.set_err(LaunchError::Replay(err));
+                        }
+                    }
                 }
             }
 
@@ -49,6 +65,14 @@ impl eframe::App for App {
                 game.update(ctx);
             }
 
+            App::Replay(replay) => {
+                replay.update(ctx);
+
+                if replay.back_clicked {
+                    *self = Self::Launcher(Launcher::default());
+                }
+            }
+
             App::None => unreachable!(),
         }
     }
@@ -60,7 +84,9 @@ pub struct Launcher {
     nickname: String,
     color: Color32,
     join_clicked: bool,
-    err: Option<JoinError>,
+    replay_path: String,
+    watch_clicked: bool,
+    err: Option<LaunchError>,
 }
 
 impl Launcher {
@@ -83,6 +109,16 @@ impl Launcher {
 
                 self.join_clicked = ui.button("join").clicked();
 
+                ui.separator();
+
+                ui.add(
+                    TextEdit::singleline(&mut self.replay_path)
+                        .horizontal_align(Align::Center)
+                        .hint_text("replay file"),
+                );
+
+                self.watch_clicked = ui.button("watch replay").clicked();
+
                 if let Some(err) = self.err {
                     ui.colored_label(Color32::DARK_RED, err.message());
                 }
@@ -90,17 +126,17 @@ impl Launcher {
         });
     }
 
-    pub fn try_start(self) -> Result<Game, (Launcher, JoinError)> {
+    pub fn try_start(self) -> Result<Game, (Launcher, LaunchError)> {
         let Ok(addr) = SocketAddr::from_str(&self.server_ip) else {
-            return Err((self, JoinError::InvalidSocketAddr));
+            return Err((self, LaunchError::InvalidSocketAddr));
         };
 
         if self.nickname.is_empty() {
-            return Err((self, JoinError::EmptyNickname));
+            return Err((self, LaunchError::EmptyNickname));
         }
 
         let Ok(mut socket) = TcpStream::connect(addr) else {
-            return Err((self, JoinError::WrongSocketAddr));
+            return Err((self, LaunchError::WrongSocketAddr));
         };
 
         let mut buffer = Vec::new();
@@ -115,11 +151,11 @@ impl Launcher {
 
         let state = Arc::new(State::default());
 
-        let (dir_tx, dir_rx) = mpsc::channel();
+        let (updates_tx, updates_rx) = mpsc::channel();
 
         {
             let state = Arc::clone(&state);
-            thread::spawn(move || StateUpdater::new(state, socket, dir_rx).receive());
+            thread::spawn(move || StateUpdater::new(state, socket, updates_rx).receive());
         }
 
         Ok(Game {
@@ -127,12 +163,13 @@ impl Launcher {
             self_id: start.self_id,
             transform: TSTransform::IDENTITY,
             last_dir_upd: Instant::now(),
-            dir_tx,
-            world_size: start.world_size,
+            boost: false,
+            updates_tx,
+            config: start.config,
         })
     }
 
-    pub fn set_err(&mut self, err: JoinError) {
+    pub fn set_err(&mut self, err: LaunchError) {
         self.err = Some(err);
     }
 }
@@ -142,8 +179,9 @@ pub struct Game {
     pub self_id: SlitherID,
     pub transform: TSTransform,
     pub last_dir_upd: Instant,
-    pub dir_tx: mpsc::Sender<f32>,
-    pub world_size: Pos2,
+    pub boost: bool,
+    pub updates_tx: mpsc::Sender<protocol::ClientUpdate>,
+    pub config: GameConfig,
 }
 
 impl Game {
@@ -158,6 +196,8 @@ impl Game {
             if self.can_update_dir() {
                 self.update_dir(ctx);
             }
+
+            self.update_boost(ctx);
         }
 
         Self::panel().show(ctx, |ui| {
@@ -170,6 +210,39 @@ impl Game {
 
             self.draw(&painter);
         });
+
+        self.kill_feed(ctx);
+        self.power_ups(ctx);
+    }
+
+    fn power_ups(&self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("power-ups"))
+            .anchor(Align2::LEFT_TOP, egui::vec2(10., 10.))
+            .interactable(false)
+            .show(ctx, |ui| {
+                self.state.world.lock_with(|world| {
+                    if world.slithers.exists(self.self_id) {
+                        painter::power_up_labels(ui, &world.slithers[self.self_id]);
+                    }
+                })
+            });
+    }
+
+    fn kill_feed(&self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("kill feed"))
+            .anchor(Align2::RIGHT_TOP, egui::vec2(-10., 10.))
+            .interactable(false)
+            .show(ctx, |ui| {
+                self.state.kills.lock_with(|kills| {
+                    for (time, kill) in kills {
+                        if time.elapsed() > KILL_FEED_TIME {
+                            break;
+                        }
+
+                        ui.colored_label(Color32::LIGHT_GRAY, kill_message(kill));
+                    }
+                })
+            });
     }
 
     fn can_update_dir(&self) -> bool {
@@ -186,7 +259,22 @@ impl Game {
 
             let dir = (virtual_mouse_pos - self.head_pos()).angle();
 
-            self.dir_tx.send(dir).unwrap();
+            self.updates_tx
+                .send(protocol::ClientUpdate::Direction(dir))
+                .unwrap();
+        }
+    }
+
+    /// boosts while the left mouse button or space is held
+    fn update_boost(&mut self, ctx: &egui::Context) {
+        let boost = ctx.input(|i| i.pointer.primary_down() || i.key_down(Key::Space));
+
+        if boost != self.boost {
+            self.boost = boost;
+
+            self.updates_tx
+                .send(protocol::ClientUpdate::Boost(boost))
+                .unwrap();
         }
     }
 
@@ -197,23 +285,15 @@ impl Game {
     }
 
     fn draw(&self, painter: &Painter) {
-        painter.rect(
-            Rect::from_min_max(Pos2::ZERO, self.world_size),
-            Color32::from_gray(30),
-            Stroke::new(2.0, Color32::from_gray(10)),
-        );
+        let copies = painter.copies(&self.config.arena);
 
-        self.state.world.lock_with(|world| {
-            for clot in world.clots.iter() {
-                let color = clot.color.linear_multiply(0.3);
-
-                painter.circle(clot.pos, clot.radius(), color);
-            }
+        for painter in &copies {
+            painter.arena(&self.config.arena);
+        }
 
-            for (_, slither) in world.slithers.iter() {
-                for &cell in slither.body.cells() {
-                    painter.circle(cell, slither.body.cell_radius(), slither.color);
-                }
+        self.state.world.lock_with(|world| {
+            for painter in &copies {
+                painter.world(world, &self.config);
             }
         });
     }
@@ -228,19 +308,33 @@ impl Game {
     }
 }
 
+fn kill_message(kill: &protocol::Kill) -> String {
+    let victim = &kill.victim;
+    let killer = kill.killer.as_deref().unwrap_or_default();
+
+    match kill.cause {
+        DeathCause::Wall => format!("{victim} crashed into the wall"),
+        DeathCause::Obstacle => format!("{victim} crashed into an obstacle"),
+        DeathCause::Body(_) => format!("{killer} killed {victim}"),
+        DeathCause::HeadOn(_) => format!("{victim} crashed head-on into {killer}"),
+    }
+}
+
 #[derive(Clone, Copy)]
-pub enum JoinError {
+pub enum LaunchError {
     EmptyNickname,
     InvalidSocketAddr,
     WrongSocketAddr,
+    Replay(ReplayError),
 }
 
-impl JoinError {
+impl LaunchError {
     pub fn message(self) -> &'static str {
         match self {
-            JoinError::EmptyNickname => "error: empty nickname",
-            JoinError::InvalidSocketAddr => "error: invalid socket address",
-            JoinError::WrongSocketAddr => "error: wrong socket address",
+            LaunchError::EmptyNickname => "error: empty nickname",
+            LaunchError::InvalidSocketAddr => "error: invalid socket address",
+            LaunchError::WrongSocketAddr => "error: wrong socket address",
+            LaunchError::Replay(err) => err.message(),
         }
     }
 }
@@ -1,6 +1,7 @@
 mod app;
 mod mutex_ext;
 mod painter;
+mod replay;
 mod state;
 mod transfer;
 
@@ -1,6 +1,11 @@
 use egui::emath::TSTransform;
-use egui::epaint::{CircleShape, RectShape};
-use egui::{Color32, Pos2, Rect, Rounding, Stroke};
+use egui::epaint::{CircleShape, PathShape, RectShape};
+use egui::{Color32, Pos2, Rect, Rounding, Stroke, Vec2};
+
+use core::{Arena, GameConfig, Obstacle, Pickup, PowerUp, Slither, World};
+
+/// How long ago a drifting clot was where its streak starts, in seconds
+const DRIFT_STREAK_TIME: f32 = 0.1;
 
 #[derive(Clone)]
 pub struct Painter {
@@ -22,6 +27,125 @@ impl Painter {
         });
     }
 
+    pub fn arena(&self, arena: &Arena) {
+        let fill = Color32::from_gray(30);
+        let stroke = Stroke::new(2.0, Color32::from_gray(10));
+
+        match *arena {
+            Arena::Rect { .. } => {
+                self.rect(Rect::from_min_max(Pos2::ZERO, arena.size()), fill, stroke)
+            }
+
+            Arena::Circle { radius } => self.draw(CircleShape {
+                center: arena.center(),
+                radius,
+                fill,
+                stroke,
+            }),
+
+            Arena::Torus { .. } => self.rect(
+                Rect::from_min_max(Pos2::ZERO, arena.size()),
+                fill,
+                Stroke::NONE,
+            ),
+        }
+    }
+
+    /// Painters for all the copies of a wrapping arena seen on the screen,
+    /// so things are drawn on both sides of an edge. It's only this one if the arena doesn't wrap
+    pub fn copies(&self, arena: &Arena) -> Vec<Painter> {
+        if !arena.wraps() {
+            return vec![self.clone()];
+        }
+
+        let size = arena.size().to_vec2();
+        let seen = self.transform.inverse() * self.raw.clip_rect();
+
+        let mut copies = Vec::new();
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let shift = Vec2::new(x as f32 * size.x, y as f32 * size.y);
+                let rect = Rect::from_min_size(Pos2::ZERO + shift, size);
+
+                if rect.intersects(seen) {
+                    copies.push(Painter {
+                        raw: self.raw.clone(),
+                        transform: self.transform * TSTransform::from_translation(shift),
+                    });
+                }
+            }
+        }
+
+        copies
+    }
+
+    pub fn world(&self, world: &World, config: &GameConfig) {
+        for clot in world.clots.iter() {
+            let color = clot.color.linear_multiply(0.3);
+
+            // a drifting clot leaves a short streak behind
+            if let Some(drift) = clot.drift {
+                let tail = clot.pos - drift * DRIFT_STREAK_TIME;
+                let stroke = Stroke::new(clot.radius(), color.gamma_multiply(0.5));
+
+                self.draw(egui::Shape::line_segment([tail, clot.pos], stroke));
+            }
+
+            self.circle(clot.pos, clot.radius(), color);
+        }
+
+        for obstacle in &config.obstacles {
+            self.obstacle(obstacle);
+        }
+
+        for pickup in &world.pickups {
+            self.pickup(pickup);
+        }
+
+        for (_, slither) in world.slithers.iter() {
+            let color = slither_color(slither);
+
+            for &cell in slither.body.cells() {
+                self.circle(cell, slither.body.cell_radius(config), color);
+            }
+        }
+    }
+
+    pub fn pickup(&self, pickup: &Pickup) {
+        self.draw(CircleShape {
+            center: pickup.pos,
+            radius: Pickup::RADIUS,
+            fill: power_up_color(pickup.power_up),
+            stroke: Stroke::new(2., Color32::WHITE),
+        });
+    }
+
+    pub fn obstacle(&self, obstacle: &Obstacle) {
+        let color = Color32::from_gray(70);
+
+        match obstacle {
+            &Obstacle::Circle { center, radius } => self.circle(center, radius, color),
+
+            Obstacle::Wall { points, width } => {
+                self.draw(PathShape::line(points.clone(), Stroke::new(*width, color)));
+
+                // round joints and ends
+                for &point in points {
+                    self.circle(point, width / 2., color);
+                }
+            }
+
+            Obstacle::Polygon { points } => {
+                self.draw(PathShape::convex_polygon(
+                    points.clone(),
+                    color,
+                    Stroke::NONE,
+                ));
+            }
+        }
+    }
+
     pub fn draw(&self, shape: impl Into<egui::Shape>) {
         let mut shape = shape.into();
 
@@ -30,3 +154,39 @@ impl Painter {
         self.raw.add(shape);
     }
 }
+
+pub fn power_up_color(power_up: PowerUp) -> Color32 {
+    match power_up {
+        PowerUp::Magnet => Color32::from_rgb(80, 140, 255),
+        PowerUp::Speed => Color32::from_rgb(255, 220, 60),
+        PowerUp::Ghost => Color32::from_rgb(200, 200, 255),
+        PowerUp::DoubleFood => Color32::from_rgb(90, 230, 90),
+    }
+}
+
+/// the body color tinted by the active power-ups, a ghost is see-through
+pub fn slither_color(slither: &Slither) -> Color32 {
+    let tinted = slither
+        .power_ups
+        .keys()
+        .filter(|&&power_up| power_up != PowerUp::Ghost)
+        .fold(slither.color, |color, &power_up| {
+            color.lerp_to_gamma(power_up_color(power_up), 0.4)
+        });
+
+    if slither.has(PowerUp::Ghost) {
+        tinted.gamma_multiply(0.35)
+    } else {
+        tinted
+    }
+}
+
+/// active power-ups of the slither with seconds left
+pub fn power_up_labels(ui: &mut egui::Ui, slither: &Slither) {
+    for (&power_up, left) in &slither.power_ups {
+        ui.colored_label(
+            power_up_color(power_up),
+            format!("{} {:.0}s", power_up.name(), left.ceil()),
+        );
+    }
+}
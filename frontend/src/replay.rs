@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::Path;
+
+use egui::emath::TSTransform;
+use egui::{Align2, ComboBox, Sense, Slider};
+
+use core::{GameConfig, GameState, Input, ReplayHeader, ReplayRecord, SlitherID, REPLAY_VERSION};
+
+use crate::painter::{self, Painter};
+
+/// Plays a recorded game by simulating it again
+pub struct Replay {
+    config: GameConfig,
+    /// the recorded ticks, each one is the inputs applied before an update
+    ticks: Vec<Vec<Input>>,
+    /// serialized states with the number of ticks recorded before them
+    keyframes: Vec<(usize, Vec<u8>)>,
+
+    state: GameState,
+    /// ticks of the replay simulated so far
+    played: usize,
+    playing: bool,
+    speed: f32,
+    /// part of a tick not simulated yet
+    accumulator: f32,
+
+    camera: Camera,
+    transform: TSTransform,
+
+    pub back_clicked: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Camera {
+    /// dragged by the mouse
+    Free,
+    Follow(SlitherID),
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let bytes = fs::read(path).map_err(|_| ReplayError::CantRead)?;
+
+        let mut reader = bytes.as_slice();
+
+        let header: ReplayHeader =
+            bincode::deserialize_from(&mut reader).map_err(|_| ReplayError::Broken)?;
+
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::OtherVersion);
+        }
+
+        let mut ticks = Vec::new();
+        let mut keyframes = Vec::new();
+
+        // the file may be cut off if the server was killed, so the broken end is dropped
+        while let Ok(record) = bincode::deserialize_from(&mut reader) {
+            match record {
+                ReplayRecord::Tick => {
+                    let Ok(inputs) = bincode::deserialize_from(&mut reader) else {
+                        break;
+                    };
+
+                    ticks.push(inputs);
+                }
+
+                ReplayRecord::Keyframe => {
+                    let start = reader;
+
+                    if bincode::deserialize_from::<_, GameState>(&mut reader).is_err() {
+                        break;
+                    }
+
+                    let len = start.len() - reader.len();
+
+                    keyframes.push((ticks.len(), start[..len].to_vec()));
+                }
+            }
+        }
+
+        let Some((_, first)) = keyframes.first() else {
+            return Err(ReplayError::Broken);
+        };
+
+        let state = restore(first, &header.config);
+
+        Ok(Self {
+            config: header.config,
+            ticks,
+            keyframes,
+            state,
+            played: 0,
+            playing: true,
+            speed: 1.,
+            accumulator: 0.,
+            camera: Camera::Free,
+            transform: TSTransform::IDENTITY,
+            back_clicked: false,
+        })
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) {
+        ctx.request_repaint();
+
+        if self.playing {
+            let frame_time = ctx.input(|i| i.stable_dt);
+
+            self.accumulator += frame_time * self.speed * self.config.tick_rate;
+
+            while self.accumulator >= 1. && self.step() {
+                self.accumulator -= 1.;
+            }
+
+            if self.played == self.ticks.len() {
+                self.playing = false;
+                self.accumulator = 0.;
+            }
+        }
+
+        self.controls(ctx);
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(egui::Color32::from_gray(20)))
+            .show(ctx, |ui| {
+                let (response, painter) =
+                    ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+
+                self.move_camera(&response);
+
+                let painter = Painter {
+                    raw: painter,
+                    transform: self.transform,
+                };
+
+                let copies = painter.copies(&self.config.arena);
+
+                for painter in &copies {
+                    painter.arena(&self.config.arena);
+                }
+
+                for painter in &copies {
+                    painter.world(&self.state.world, &self.config);
+                }
+            });
+
+        self.followed_info(ctx);
+    }
+
+    fn controls(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("replay controls").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("back").clicked() {
+                    self.back_clicked = true;
+                }
+
+                let play = if self.playing { "pause" } else { "play" };
+
+                if ui.button(play).clicked() {
+                    self.playing = !self.playing;
+
+                    if self.playing && self.played == self.ticks.len() {
+                        self.seek(0);
+                    }
+                }
+
+                ui.add(
+                    Slider::new(&mut self.speed, 0.25..=8.)
+                        .logarithmic(true)
+                        .text("speed"),
+                );
+
+                let mut tick = self.played;
+
+                let time = |tick: f64| {
+                    let seconds = tick / self.config.tick_rate as f64;
+
+                    format!("{}:{:02}", seconds as u64 / 60, seconds as u64 % 60)
+                };
+
+                let timeline = Slider::new(&mut tick, 0..=self.ticks.len())
+                    .custom_formatter(|tick, _| time(tick));
+
+                if ui.add(timeline).changed() {
+                    self.seek(tick);
+                }
+
+                self.camera_choice(ui);
+            });
+        });
+    }
+
+    fn camera_choice(&mut self, ui: &mut egui::Ui) {
+        let world = &self.state.world;
+
+        let selected = match self.camera {
+            Camera::Free => "free camera".to_owned(),
+            Camera::Follow(id) => world.slithers.get(id).nickname.clone(),
+        };
+
+        ComboBox::from_id_source("camera")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.camera, Camera::Free, "free camera");
+
+                for (id, slither) in world.slithers.iter() {
+                    ui.selectable_value(&mut self.camera, Camera::Follow(id), &slither.nickname);
+                }
+            });
+    }
+
+    fn move_camera(&mut self, response: &egui::Response) {
+        match self.camera {
+            Camera::Free => {
+                self.transform.translation += response.drag_delta();
+            }
+
+            Camera::Follow(id) => {
+                let head = self.state.world.slithers.get(id).body.head();
+                let screen_center = response.rect.center();
+
+                self.transform.translation = screen_center.to_vec2() - head.to_vec2();
+            }
+        }
+    }
+
+    fn followed_info(&self, ctx: &egui::Context) {
+        if let Camera::Follow(id) = self.camera {
+            egui::Area::new(egui::Id::new("followed"))
+                .anchor(Align2::LEFT_TOP, egui::vec2(10., 10.))
+                .interactable(false)
+                .show(ctx, |ui| {
+                    let slither = self.state.world.slithers.get(id);
+
+                    ui.label(format!("mass: {:.0}", slither.body.mass()));
+                    ui.label(format!("kills: {}", slither.kills));
+
+                    painter::power_up_labels(ui, slither);
+                });
+        }
+    }
+
+    /// simulates the next tick, returns false at the end of the replay
+    fn step(&mut self) -> bool {
+        let Some(inputs) = self.ticks.get(self.played) else {
+            return false;
+        };
+
+        for input in inputs.iter().cloned() {
+            self.state.apply(input);
+        }
+
+        self.state.update(self.config.delta_time());
+        self.state.drain_events();
+
+        self.played += 1;
+
+        self.follow_check();
+
+        true
+    }
+
+    /// goes to the tick from the nearest keyframe before it
+    fn seek(&mut self, tick: usize) {
+        if tick < self.played || self.keyframe_before(tick) > self.played {
+            let (played, bytes) = &self.keyframes[self.keyframe_index(tick)];
+
+            self.state = restore(bytes, &self.config);
+            self.played = *played;
+        }
+
+        while self.played < tick && self.step() {}
+
+        self.accumulator = 0.;
+
+        self.follow_check();
+    }
+
+    fn keyframe_index(&self, tick: usize) -> usize {
+        self.keyframes
+            .partition_point(|&(played, _)| played <= tick)
+            .saturating_sub(1)
+    }
+
+    fn keyframe_before(&self, tick: usize) -> usize {
+        self.keyframes[self.keyframe_index(tick)].0
+    }
+
+    /// a followed slither may be gone, then the camera stays where it is
+    fn follow_check(&mut self) {
+        if let Camera::Follow(id) = self.camera {
+            if !self.state.world.slithers.exists(id) {
+                self.camera = Camera::Free;
+            }
+        }
+    }
+}
+
+fn restore(bytes: &[u8], config: &GameConfig) -> GameState {
+    let mut state: GameState = bincode::deserialize(bytes).unwrap();
+
+    state.config = config.clone();
+
+    state
+}
+
+#[derive(Clone, Copy)]
+pub enum ReplayError {
+    CantRead,
+    OtherVersion,
+    Broken,
+}
+
+impl ReplayError {
+    pub fn message(self) -> &'static str {
+        match self {
+            ReplayError::CantRead => "error: can't read the replay file",
+            ReplayError::OtherVersion => "error: the replay is recorded by another version",
+            ReplayError::Broken => "error: the replay file is broken",
+        }
+    }
+}
@@ -1,18 +1,25 @@
+use std::collections::VecDeque;
 use std::net::TcpStream;
 use std::sync::atomic;
 use std::sync::atomic::AtomicBool;
 use std::sync::{mpsc, Arc, Mutex};
+use std::time::Instant;
 
 use core::{SlitherID, World};
 
 use crate::mutex_ext::MutexExt;
 use crate::transfer::SyncSend;
 
+/// How many kills the feed keeps
+const KILL_FEED_LEN: usize = 5;
+
 #[derive(Default)]
 pub struct State {
     pub world: Mutex<World>,
     pub game_over: AtomicBool,
     pub top: Mutex<Vec<SlitherID>>,
+    /// recent kills with the time they were received, the newest go first
+    pub kills: Mutex<VecDeque<(Instant, protocol::Kill)>>,
 }
 
 impl State {
@@ -24,17 +31,21 @@ impl State {
 pub struct StateUpdater {
     state: Arc<State>,
     socket: TcpStream,
-    dir_rx: mpsc::Receiver<f32>,
+    updates_rx: mpsc::Receiver<protocol::ClientUpdate>,
 
     buffer: Vec<u8>,
 }
 
 impl StateUpdater {
-    pub fn new(state: Arc<State>, socket: TcpStream, dir_rx: mpsc::Receiver<f32>) -> Self {
+    pub fn new(
+        state: Arc<State>,
+        socket: TcpStream,
+        updates_rx: mpsc::Receiver<protocol::ClientUpdate>,
+    ) -> Self {
         Self {
             state,
             socket,
-            dir_rx,
+            updates_rx,
             buffer: Vec::new(),
         }
     }
@@ -54,6 +65,15 @@ impl StateUpdater {
                     self.state.top.lock_with_mut(move |top| *top = new_top);
                 }
 
+                protocol::ServerUpdate::Kill => {
+                    let kill = bincode::deserialize_from(&mut self.socket).unwrap();
+
+                    self.state.kills.lock_with_mut(move |kills| {
+                        kills.push_front((Instant::now(), kill));
+                        kills.truncate(KILL_FEED_LEN);
+                    });
+                }
+
                 protocol::ServerUpdate::World => {
                     let new_world = bincode::deserialize_from(&mut self.socket).unwrap();
 
@@ -63,8 +83,8 @@ impl StateUpdater {
                 }
             }
 
-            if let Ok(dir) = self.dir_rx.try_recv() {
-                protocol::ClientUpdate::Direction(dir).send(&mut self.buffer, &mut self.socket);
+            while let Ok(update) = self.updates_rx.try_recv() {
+                update.send(&mut self.buffer, &mut self.socket);
             }
         }
     }
@@ -21,7 +21,7 @@ pub trait SyncSend: Serialize {
 }
 
 pub trait SyncReceive: DeserializeOwned {
-    fn receive(mut buffer: &mut Vec<u8>, reader: &mut impl Read) -> Self {
+    fn receive(buffer: &mut Vec<u8>, reader: &mut impl Read) -> Self {
         buffer.clear();
 
         let packet_size = {
@@ -32,7 +32,7 @@ pub trait SyncReceive: DeserializeOwned {
 
         buffer.resize(packet_size as usize, 0);
 
-        reader.read_exact(&mut buffer).unwrap();
+        reader.read_exact(buffer).unwrap();
 
         bincode::deserialize_from(buffer.as_slice()).unwrap()
     }
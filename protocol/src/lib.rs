@@ -1,7 +1,6 @@
-use core::SlitherID;
+use core::{DeathCause, GameConfig, SlitherID};
 
 use ecolor::Color32;
-use emath::Pos2;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -13,13 +12,29 @@ pub struct PlayerJoin {
 #[derive(Serialize, Deserialize)]
 pub enum ClientUpdate {
     Direction(f32),
+    /// starts or stops boosting
+    Boost(bool),
     Disconnect,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SessionStart {
-    pub world_size: Pos2,
     pub self_id: SlitherID,
+    /// rules of the game without the seed
+    pub config: GameConfig,
+}
+
+impl SessionStart {
+    /// The seed is cleared, with it a client could predict where food, pickups and slithers appear
+    pub fn new(self_id: SlitherID, config: &GameConfig) -> Self {
+        Self {
+            self_id,
+            config: GameConfig {
+                seed: 0,
+                ..config.clone()
+            },
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,4 +42,13 @@ pub enum ServerUpdate {
     GameOver,
     World,
     PlayersTop,
+    /// followed by [`Kill`]
+    Kill,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Kill {
+    pub victim: String,
+    pub killer: Option<String>,
+    pub cause: DeathCause,
 }
@@ -0,0 +1,101 @@
+mod stats;
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use ecolor::Color32;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use core::cli::{self, arg};
+use core::{GameConfig, GameState, Input, Map, SlitherID};
+
+use stats::Stats;
+
+/// How often the world is sampled, in seconds of the game
+const SAMPLE_PERIOD: f32 = 10.;
+
+/// Runs the game without players and prints how it went
+fn main() {
+    let ticks = arg("--ticks").unwrap_or(6000);
+    let slithers = arg("--slithers").unwrap_or(10);
+
+    let mut config = arg::<PathBuf>("--config")
+        .map(|path| load(&path))
+        .unwrap_or_default();
+
+    if let Some(seed) = arg("--seed") {
+        config.seed = seed;
+    }
+
+    let mut game_state = {
+        let rng = config.rng();
+        let map = Map::from_config(&config);
+
+        GameState::from_map(config, map, rng)
+    };
+
+    // walkers have their own randomness, so they don't change the game's one
+    let mut rng = StdRng::seed_from_u64(game_state.config.seed);
+
+    let mut stats = Stats::default();
+    let mut next_id = 0;
+
+    let sample_interval = ((game_state.config.tick_rate * SAMPLE_PERIOD) as u64).max(1);
+    let delta_time = game_state.config.delta_time();
+
+    let start = Instant::now();
+
+    for _ in 0..ticks {
+        // the dead are replaced, so the population stays the same
+        while game_state.world.slithers.iter().count() < slithers {
+            game_state.apply(Input::Join {
+                id: SlitherID(next_id),
+                color: Color32::WHITE,
+                nickname: format!("walker {next_id}"),
+            });
+
+            next_id += 1;
+        }
+
+        for input in random_walk(&game_state, &mut rng) {
+            game_state.apply(input);
+        }
+
+        if game_state.tick().is_multiple_of(sample_interval) {
+            stats.sample(&game_state);
+        }
+
+        game_state.update(delta_time);
+
+        stats.update(&mut game_state);
+    }
+
+    stats.sample(&game_state);
+    stats.print(&game_state, start.elapsed());
+}
+
+/// Every slither turns a bit to a random side and sometimes boosts
+fn random_walk(game_state: &GameState, rng: &mut impl Rng) -> Vec<Input> {
+    let mut inputs = Vec::new();
+
+    for (id, slither) in game_state.world.slithers.iter() {
+        let turn = rng.gen_range(-0.3..0.3);
+
+        inputs.push(Input::Direction(id, slither.body.dir() + turn));
+        inputs.push(Input::Boost(id, rng.gen_bool(0.05)));
+    }
+
+    inputs
+}
+
+/// Reads the config from a TOML file, missing fields keep default values
+fn load(path: &Path) -> GameConfig {
+    let config: GameConfig = cli::load(path, "config");
+
+    if let Err(err) = config.validate() {
+        cli::invalid(path, "config", err);
+    }
+
+    config
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use core::{GameEvent, GameState, SlitherID};
+
+/// What is measured during a simulation
+#[derive(Default)]
+pub struct Stats {
+    /// ticks when the alive slithers were spawned
+    spawned: BTreeMap<SlitherID, u64>,
+    /// lifetimes of the died slithers in ticks
+    lifetimes: Vec<u64>,
+    peak_mass: f32,
+    /// world mass, clots count and slithers count taken from time to time
+    samples: Vec<Sample>,
+}
+
+struct Sample {
+    tick: u64,
+    world_mass: f32,
+    clots: usize,
+    slithers: usize,
+}
+
+impl Stats {
+    /// must be called after every update
+    pub fn update(&mut self, game_state: &mut GameState) {
+        let tick = game_state.tick();
+
+        for event in game_state.drain_events() {
+            match event {
+                GameEvent::SlitherSpawned { id } => {
+                    self.spawned.insert(id, tick);
+                }
+
+                GameEvent::SlitherDied(death) => {
+                    let spawned = self.spawned.remove(&death.id).unwrap();
+
+                    self.lifetimes.push(tick - spawned);
+                }
+
+                _ => {}
+            }
+        }
+
+        for (_, slither) in game_state.world.slithers.iter() {
+            self.peak_mass = self.peak_mass.max(slither.body.mass());
+        }
+    }
+
+    pub fn sample(&mut self, game_state: &GameState) {
+        let world = &game_state.world;
+
+        self.samples.push(Sample {
+            tick: game_state.tick(),
+            world_mass: world.mass(),
+            clots: world.clots.len(),
+            slithers: world.slithers.iter().count(),
+        });
+    }
+
+    /// in ticks, `None` if nobody died
+    fn average_lifetime(&self) -> Option<f32> {
+        if self.lifetimes.is_empty() {
+            return None;
+        }
+
+        Some(self.lifetimes.iter().sum::<u64>() as f32 / self.lifetimes.len() as f32)
+    }
+
+    /// the fewest and the most clots over the samples
+    fn clots_range(&self) -> (usize, usize) {
+        let clots = self.samples.iter().map(|sample| sample.clots);
+
+        (
+            clots.clone().min().unwrap_or_default(),
+            clots.max().unwrap_or_default(),
+        )
+    }
+
+    pub fn print(&self, game_state: &GameState, elapsed: Duration) {
+        let tick_rate = game_state.config.tick_rate;
+        let ticks = game_state.tick();
+
+        println!(
+            "{:>8} {:>12} {:>8} {:>9}",
+            "time", "world mass", "clots", "slithers"
+        );
+
+        for sample in &self.samples {
+            println!(
+                "{:>7.1}s {:>12.1} {:>8} {:>9}",
+                sample.tick as f32 / tick_rate,
+                sample.world_mass,
+                sample.clots,
+                sample.slithers,
+            );
+        }
+
+        println!();
+
+        match self.average_lifetime() {
+            Some(average) => println!(
+                "average lifetime: {:.1}s over {} deaths",
+                average / tick_rate,
+                self.lifetimes.len()
+            ),
+
+            None => println!("average lifetime: nobody died"),
+        }
+
+        let (min_clots, max_clots) = self.clots_range();
+
+        println!("peak mass: {:.1}", self.peak_mass);
+        println!("clots: {min_clots} min, {max_clots} max");
+        println!(
+            "ticks per second: {:.0}",
+            ticks as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecolor::Color32;
+
+    use core::{Arena, GameConfig, GameState, Input, SlitherID, World};
+
+    use super::{Sample, Stats};
+
+    fn sample(clots: usize) -> Sample {
+        Sample {
+            tick: 0,
+            world_mass: 0.,
+            clots,
+            slithers: 0,
+        }
+    }
+
+    #[test]
+    fn figures_are_aggregated() {
+        let stats = Stats {
+            lifetimes: vec![60, 120, 300],
+            samples: vec![sample(40), sample(10), sample(25)],
+            ..Default::default()
+        };
+
+        assert_eq!(stats.average_lifetime(), Some(160.));
+        assert_eq!(stats.clots_range(), (10, 40));
+    }
+
+    #[test]
+    fn nothing_to_aggregate() {
+        let stats = Stats::default();
+
+        assert_eq!(stats.average_lifetime(), None);
+        assert_eq!(stats.clots_range(), (0, 0));
+    }
+
+    #[test]
+    fn lifetime_is_counted_from_spawn_to_death() {
+        // so small the slither soon hits a wall
+        let config = GameConfig {
+            arena: Arena::Rect {
+                width: 100.,
+                height: 100.,
+            },
+            ..Default::default()
+        };
+
+        let rng = config.rng();
+        let mut state = GameState::new(config, World::default(), rng);
+        let mut stats = Stats::default();
+
+        state.apply(Input::Join {
+            id: SlitherID(0),
+            color: Color32::WHITE,
+            nickname: String::new(),
+        });
+
+        while state.world.slithers.exists(SlitherID(0)) {
+            state.update(state.config.delta_time());
+            stats.update(&mut state);
+
+            assert!(state.tick() < 600);
+        }
+
+        assert!(stats.spawned.is_empty());
+        assert_eq!(stats.lifetimes.len(), 1);
+        assert!(stats.lifetimes[0] < state.tick());
+        assert!(stats.peak_mass >= state.config.init_slither_mass);
+    }
+}